@@ -0,0 +1,38 @@
+uniform mat3 u_projection_matrix;
+uniform mat3 u_view_matrix;
+uniform mat3 u_model_matrix;
+varying vec2 v_quad_pos;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+void main() {
+    v_quad_pos = a_pos;
+    vec3 pos = u_projection_matrix * u_view_matrix * u_model_matrix * vec3(a_pos, 1.0);
+    gl_Position = vec4(pos.xy, 0.0, pos.z);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform float u_intensity;
+// Direction of the cone, and the cosine of its half-angle (-1 for a point light)
+uniform vec2 u_direction;
+uniform float u_cone;
+
+// How far past the edge of the cone the light fades out
+#define CONE_SOFTNESS 0.1
+
+void main() {
+    float dist = length(v_quad_pos);
+    if (dist > 1.0) {
+        discard;
+    }
+
+    float light = (1.0 - dist) * u_intensity;
+    if (dist > 0.0) {
+        float angle = dot(v_quad_pos / dist, u_direction);
+        light *= clamp((angle - u_cone) / CONE_SOFTNESS + 1.0, 0.0, 1.0);
+    }
+
+    gl_FragColor = vec4(light);
+}
+#endif
@@ -15,10 +15,14 @@ void main() {
 uniform sampler2D u_mask_texture;
 uniform sampler2D u_color_texture;
 
+// Number of distinct light levels, keeps the falloff in crisp bands
+#define LIGHT_STEPS 4.0
+
 void main() {
     vec4 mask = texture2D(u_mask_texture, v_uv);
     vec4 color = texture2D(u_color_texture, v_uv);
-    color *= mask;
+    float light = ceil(clamp(mask.a, 0.0, 1.0) * LIGHT_STEPS) / LIGHT_STEPS;
+    color.a *= light;
     if (color.a < 0.1) {
         discard;
     }
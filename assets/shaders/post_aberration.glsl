@@ -0,0 +1,26 @@
+varying vec2 v_vt;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+attribute vec2 a_vt;
+
+void main() {
+    v_vt = a_vt;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_texture;
+uniform vec2 u_texture_size;
+uniform float u_offset;
+
+void main() {
+    // Split grows towards the edges of the screen
+    vec2 offset = (v_vt - 0.5) * 2.0 * u_offset / u_texture_size;
+    vec4 color = texture2D(u_texture, v_vt);
+    color.r = texture2D(u_texture, v_vt + offset).r;
+    color.b = texture2D(u_texture, v_vt - offset).b;
+    gl_FragColor = color;
+}
+#endif
@@ -0,0 +1,45 @@
+varying vec2 v_vt;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+attribute vec2 a_vt;
+
+void main() {
+    v_vt = a_vt;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_texture;
+uniform vec2 u_texture_size;
+uniform float u_threshold;
+uniform float u_intensity;
+
+// Radius of the glow, in steps of two pixels
+#define RADIUS 4
+
+vec3 bright(vec2 uv) {
+    vec3 color = texture2D(u_texture, uv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    return color * step(u_threshold, brightness);
+}
+
+void main() {
+    vec4 color = texture2D(u_texture, v_vt);
+    vec2 pixel = 2.0 / u_texture_size;
+
+    vec3 glow = vec3(0.0);
+    float total = 0.0;
+    for (int x = -RADIUS; x <= RADIUS; x++) {
+        for (int y = -RADIUS; y <= RADIUS; y++) {
+            vec2 offset = vec2(float(x), float(y));
+            float weight = max(0.0, 1.0 - length(offset) / float(RADIUS));
+            glow += bright(v_vt + offset * pixel) * weight;
+            total += weight;
+        }
+    }
+
+    gl_FragColor = vec4(color.rgb + glow / total * u_intensity, color.a);
+}
+#endif
@@ -0,0 +1,22 @@
+varying vec2 v_vt;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+attribute vec2 a_vt;
+
+void main() {
+    v_vt = a_vt;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_texture;
+uniform float u_levels;
+
+void main() {
+    vec4 color = texture2D(u_texture, v_vt);
+    float steps = max(u_levels - 1.0, 1.0);
+    gl_FragColor = vec4(floor(color.rgb * steps + 0.5) / steps, color.a);
+}
+#endif
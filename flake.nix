@@ -43,6 +43,8 @@
             gcc
             openssl
             pkg-config
+            # Golden frame tests, see scripts/golden.sh
+            xvfb-run
             # rust-bin.stable.latest.default
             (rust-bin.stable.latest.default.override {
               extensions = [ "rust-src" ];
@@ -0,0 +1,43 @@
+#!/usr/bin/env bash
+# Render every scenario in assets/scenarios and compare it with the reference
+# image next to it. Pass --bless to write the references from the new frames.
+#
+# Uses Mesa's software rasterizer, under a virtual X display if there is none,
+# so the frames do not depend on the GPU.
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+bless=false
+if [[ "${1:-}" == "--bless" ]]; then
+    bless=true
+fi
+
+export LIBGL_ALWAYS_SOFTWARE=1
+run=()
+if [[ -z "${DISPLAY:-}" ]]; then
+    run=(xvfb-run -a -s "-screen 0 1280x720x24")
+fi
+
+cargo build --release
+binary=target/release/drillraph
+mkdir -p target/golden
+
+failed=0
+for scenario in assets/scenarios/*.ron; do
+    name=$(basename "$scenario" .ron)
+    reference="assets/scenarios/$name.png"
+    out="target/golden/$name.png"
+    if $bless; then
+        "${run[@]}" "$binary" --render-frame "$scenario" --out "$reference"
+        echo "wrote $reference"
+    elif [[ ! -f "$reference" ]]; then
+        echo "FAILED $name, no reference image, run with --bless to write it"
+        failed=1
+    elif "${run[@]}" "$binary" --render-frame "$scenario" --out "$out" --reference "$reference"; then
+        echo "ok $name"
+    else
+        echo "FAILED $name, see $out"
+        failed=1
+    fi
+done
+exit $failed
@@ -3,8 +3,9 @@ mod font;
 pub use self::font::*;
 
 use crate::{
+    context::Theme,
     game::Controls,
-    model::{Config, ConnectionKind, ResourceKind},
+    model::{BiomeKind, Config, ConnectionKind, HazardKind, MusicLayer, ResourceKind},
     prelude::Color,
 };
 
@@ -14,7 +15,7 @@ use geng::prelude::*;
 
 #[derive(geng::asset::Load)]
 pub struct LoadingAssets {
-    pub palette: Palette,
+    pub palettes: Palettes,
     #[load(path = "sprites/title.png", options(filter = "ugli::Filter::Nearest"))]
     pub title: ugli::Texture,
     #[load(path = "fonts/default.ttf")]
@@ -25,13 +26,14 @@ pub struct LoadingAssets {
 
 #[derive(geng::asset::Load)]
 pub struct Assets {
-    pub palette: Palette,
+    pub palettes: Palettes,
     pub controls: Controls,
     pub shaders: Shaders,
     pub sprites: Sprites,
     pub config: Config,
     pub fonts: Fonts,
     pub sounds: Sounds,
+    pub music: MusicAssets,
 }
 
 impl Assets {
@@ -53,14 +55,51 @@ pub struct Sounds {
     pub stop: Rc<geng::Sound>,
 }
 
+/// Looping layers of the adaptive music, all of the same length.
+#[derive(geng::asset::Load)]
+pub struct MusicAssets {
+    #[load(options(looped = "true"))]
+    pub calm: Rc<geng::Sound>,
+    #[load(options(looped = "true"))]
+    pub bass: Rc<geng::Sound>,
+    #[load(options(looped = "true"))]
+    pub drums: Rc<geng::Sound>,
+    #[load(options(looped = "true"))]
+    pub arp: Rc<geng::Sound>,
+    #[load(options(looped = "true"))]
+    pub deep: Rc<geng::Sound>,
+}
+
+impl MusicAssets {
+    pub fn get(&self, layer: MusicLayer) -> &Rc<geng::Sound> {
+        match layer {
+            MusicLayer::Calm => &self.calm,
+            MusicLayer::Bass => &self.bass,
+            MusicLayer::Drums => &self.drums,
+            MusicLayer::Arp => &self.arp,
+            MusicLayer::Deep => &self.deep,
+        }
+    }
+
+    /// All layers in the order of [`MusicLayer::ALL`].
+    pub fn layers(&self) -> [&Rc<geng::Sound>; MusicLayer::ALL.len()] {
+        MusicLayer::ALL.map(|layer| self.get(layer))
+    }
+}
+
 #[derive(geng::asset::Load)]
 pub struct Shaders {
     pub tiled_texture: Rc<ugli::Program>,
     pub texture: Rc<ugli::Program>,
     pub ellipse: Rc<ugli::Program>,
     pub masked: Rc<ugli::Program>,
+    pub light: Rc<ugli::Program>,
     pub solid: Rc<ugli::Program>,
     pub crt: Rc<ugli::Program>,
+    pub post_shake: Rc<ugli::Program>,
+    pub post_bloom: Rc<ugli::Program>,
+    pub post_aberration: Rc<ugli::Program>,
+    pub post_quantize: Rc<ugli::Program>,
 }
 
 #[derive(geng::asset::Load)]
@@ -78,6 +117,7 @@ pub struct Fonts {
 pub struct Sprites {
     pub drill: PixelTexture,
     pub coin: PixelTexture,
+    pub chest: PixelTexture,
     pub fuel_small_node: PixelTexture,
     pub fuel_normal_node: PixelTexture,
     pub fill_thinner: PixelTexture,
@@ -88,10 +128,26 @@ pub struct Sprites {
     pub connect_dot: PixelTexture,
     pub drill_background_largedot_purple: PixelTexture,
     pub drill_background_green: PixelTexture,
+    pub background_topsoil: PixelTexture,
+    pub background_bedrock: PixelTexture,
+    pub background_caverns: PixelTexture,
+    pub background_magma: PixelTexture,
 
     pub upgrade_node: PixelTexture,
     pub battery_node: PixelTexture,
     pub coal_fuel_node: PixelTexture,
+    pub armor_node: PixelTexture,
+    pub repair_node: PixelTexture,
+    pub cargo_node: PixelTexture,
+    pub cargo_expansion_node: PixelTexture,
+    pub winch_node: PixelTexture,
+    pub elevator_node: PixelTexture,
+    pub filter_node: PixelTexture,
+    pub cooling_node: PixelTexture,
+    pub pump_node: PixelTexture,
+    pub zapper_node: PixelTexture,
+    pub scanner_node: PixelTexture,
+    pub tuner_node: PixelTexture,
 
     pub drill_iron: PixelTexture,
     pub drill_bronze: PixelTexture,
@@ -139,6 +195,26 @@ pub struct Sprites {
     pub gold_ore: PixelTexture,
 }
 
+/// Palettes to choose from with [`Theme`].
+#[derive(geng::asset::Load)]
+pub struct Palettes {
+    pub default: Palette,
+    pub deuteranopia: Palette,
+    pub tritanopia: Palette,
+    pub high_contrast: Palette,
+}
+
+impl Palettes {
+    pub fn get(&self, theme: Theme) -> &Palette {
+        match theme {
+            Theme::Default => &self.default,
+            Theme::Deuteranopia => &self.deuteranopia,
+            Theme::Tritanopia => &self.tritanopia,
+            Theme::HighContrast => &self.high_contrast,
+        }
+    }
+}
+
 #[derive(geng::asset::Load, Serialize, Deserialize, Debug, Clone)]
 #[load(serde = "toml")]
 pub struct Palette {
@@ -164,12 +240,43 @@ pub struct Palette {
     pub sprint_back: Color,
     pub sprint_front: Color,
 
+    pub time_back: Color,
+    pub time_front: Color,
+
+    pub hull_back: Color,
+    pub hull_front: Color,
+
+    pub damage: Color,
+    pub healing: Color,
+
+    pub cargo_back: Color,
+
+    pub cave: Color,
+    pub cave_edge: Color,
+    pub tunnel: Color,
+    pub tunnel_explored: Color,
+    pub minimap_explored: Color,
+
     pub rock: Color,
     pub resources: HashMap<ResourceKind, Color>,
+    pub biomes: HashMap<BiomeKind, PaletteBiome>,
+    pub hazards: HashMap<HazardKind, Color>,
+    pub creatures: HashMap<CreatureKind, Color>,
+    pub zap: Color,
+    pub scanner: Color,
+    /// Outline the ore with the color of its resource.
+    #[serde(default)]
+    pub ore_rings: bool,
 
     pub nodes: PaletteNodes,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaletteBiome {
+    pub rock: Color,
+    pub banner: Color,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PaletteNodes {
     pub connections: HashMap<ConnectionKind, Color>,
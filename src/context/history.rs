@@ -0,0 +1,57 @@
+use super::*;
+
+use crate::model::{RunEnd, RunStats};
+
+/// How many of the latest runs are kept in the history.
+const MAX_RUNS: usize = 100;
+
+/// A single finished run, as stored in the history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub end: RunEnd,
+    #[serde(default)]
+    pub stats: RunStats,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunHistory {
+    /// The latest runs, oldest first.
+    pub runs: Vec<RunRecord>,
+    /// Number of runs ever finished, including the ones dropped from the history.
+    total_runs: usize,
+    /// Records over all runs, including the ones dropped from the history.
+    best_depth: Option<Coord>,
+    best_haul: Option<Money>,
+}
+
+impl RunHistory {
+    pub fn push(&mut self, record: RunRecord) {
+        self.total_runs = self.total_runs() + 1;
+        self.best_depth = self.best_depth().max(Some(record.stats.max_depth));
+        self.best_haul = self.best_haul().max(Some(record.stats.money));
+        self.runs.push(record);
+        if self.runs.len() > MAX_RUNS {
+            self.runs.drain(..self.runs.len() - MAX_RUNS);
+        }
+    }
+
+    pub fn total_runs(&self) -> usize {
+        self.total_runs.max(self.runs.len())
+    }
+
+    pub fn best_depth(&self) -> Option<Coord> {
+        let runs = self.runs.iter().map(|run| run.stats.max_depth);
+        self.best_depth.into_iter().chain(runs).max()
+    }
+
+    pub fn best_haul(&self) -> Option<Money> {
+        let runs = self.runs.iter().map(|run| run.stats.money);
+        self.best_haul.into_iter().chain(runs).max()
+    }
+
+    /// The last `n` runs, oldest first.
+    pub fn last(&self, n: usize) -> &[RunRecord] {
+        &self.runs[self.runs.len().saturating_sub(n)..]
+    }
+}
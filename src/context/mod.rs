@@ -1,22 +1,39 @@
+mod history;
 mod music;
+mod sfx;
 
-pub use self::music::*;
+pub use self::{history::*, music::*, sfx::*};
 
-use crate::prelude::*;
+use crate::{
+    model::{Coord, Money, SaveState},
+    prelude::*,
+    game::{Controls, KeyRebinds},
+    render::post::PostOptions,
+};
 
 #[derive(Clone)]
 pub struct Context {
     pub geng: Geng,
     pub assets: Rc<Assets>,
     pub music: Rc<MusicManager>,
+    pub sfx: Rc<SfxManager>,
     options: Rc<RefCell<Options>>,
+    history: Rc<RefCell<RunHistory>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Options {
+    #[serde(default, deserialize_with = "Theme::deserialize_or_default")]
     pub theme: Theme,
     pub master_volume: f32,
     pub music_volume: f32,
+    #[serde(default)]
+    pub sfx_volumes: SfxVolumes,
+    #[serde(default)]
+    pub post: PostOptions,
+    /// Keys the player has bound to the actions instead of the default ones.
+    #[serde(default)]
+    pub rebinds: KeyRebinds,
 }
 
 impl Default for Options {
@@ -25,38 +42,101 @@ impl Default for Options {
             theme: Theme::default(),
             master_volume: 0.5,
             music_volume: 1.0,
+            sfx_volumes: SfxVolumes::default(),
+            post: PostOptions::default(),
+            rebinds: KeyRebinds::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
-pub struct Theme {
-    pub dark: Color,
-    pub light: Color,
-    pub highlight: Color,
+/// Selects the palette the game is drawn with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Default,
+    /// Red-green color blindness.
+    Deuteranopia,
+    /// Blue-yellow color blindness.
+    Tritanopia,
+    HighContrast,
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Self {
-            dark: Color::try_from("#000000").unwrap(),
-            light: Color::try_from("#ffffff").unwrap(),
-            highlight: Color::try_from("#00ffff").unwrap(),
+impl Theme {
+    pub const ALL: [Theme; 4] = [
+        Theme::Default,
+        Theme::Deuteranopia,
+        Theme::Tritanopia,
+        Theme::HighContrast,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Default => "Default",
+            Theme::Deuteranopia => "Red-green",
+            Theme::Tritanopia => "Blue-yellow",
+            Theme::HighContrast => "High contrast",
         }
     }
+
+    /// Options saved before themes were presets stored the theme colors directly,
+    /// those fall back to the default theme instead of discarding all the options.
+    fn deserialize_or_default<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Theme(Theme),
+            Legacy(serde::de::IgnoredAny),
+        }
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Theme(theme) => theme,
+            Stored::Legacy(_) => Theme::default(),
+        })
+    }
 }
 
 impl Context {
     pub async fn new(geng: &Geng, assets: &Rc<Assets>) -> Result<Self> {
         let options: Options = preferences::load(crate::OPTIONS_STORAGE).unwrap_or_default();
+        let history: RunHistory =
+            preferences::load(crate::RUN_HISTORY_STORAGE).unwrap_or_default();
+        let ctx = Self::with_state(geng, assets, Options::default(), history);
+        ctx.force_set_options(options);
+        Ok(ctx)
+    }
+
+    /// A context with the given options and run history, ignoring the stored ones.
+    pub fn with_state(
+        geng: &Geng,
+        assets: &Rc<Assets>,
+        options: Options,
+        history: RunHistory,
+    ) -> Self {
         let ctx = Self {
             geng: geng.clone(),
             assets: assets.clone(),
             music: Rc::new(MusicManager::new(geng.clone())),
-            options: Rc::new(RefCell::new(Options::default())),
+            sfx: Rc::new(SfxManager::new(geng.clone())),
+            options: Rc::new(RefCell::new(options)),
+            history: Rc::new(RefCell::new(history)),
         };
-        ctx.force_set_options(options);
-        Ok(ctx)
+        ctx.apply_volumes();
+        ctx
+    }
+
+    /// The palette of the currently selected theme.
+    pub fn palette(&self) -> &Palette {
+        let theme = self.options.borrow().theme;
+        self.assets.palettes.get(theme)
+    }
+
+    /// The controls with the player's rebinds applied.
+    pub fn controls(&self) -> Controls {
+        self.assets
+            .controls
+            .with_rebinds(&self.options.borrow().rebinds)
     }
 
     pub fn get_options(&self) -> Options {
@@ -71,13 +151,53 @@ impl Context {
         }
     }
 
+    /// Apply the options without saving them, for changes still being made.
+    pub fn preview_options(&self, options: Options) {
+        *self.options.borrow_mut() = options;
+        self.apply_volumes();
+    }
+
+    /// Save the options applied with [`Self::preview_options`].
+    pub fn save_options(&self) {
+        preferences::save(crate::OPTIONS_STORAGE, &*self.options.borrow());
+    }
+
+    /// The history of the finished runs, do not hold on to it while a run can end.
+    pub fn history(&self) -> std::cell::Ref<'_, RunHistory> {
+        self.history.borrow()
+    }
+
+    /// The deepest point reached over all runs.
+    pub fn best_depth(&self) -> Option<Coord> {
+        self.history.borrow().best_depth()
+    }
+
+    /// Append a finished run to the history and save it.
+    pub fn record_run(&self, record: RunRecord) {
+        let mut history = self.history.borrow_mut();
+        history.push(record);
+        preferences::save(crate::RUN_HISTORY_STORAGE, &*history);
+    }
+
+    /// The saved game to continue, if there is one.
+    pub fn load_save(&self) -> Option<SaveState> {
+        preferences::load(crate::SAVE_STORAGE)
+    }
+
+    pub fn store_save(&self, save: &SaveState) {
+        preferences::save(crate::SAVE_STORAGE, save);
+    }
+
     fn force_set_options(&self, options: Options) {
-        let mut old = self.options.borrow_mut();
+        preferences::save(crate::OPTIONS_STORAGE, &options);
+        *self.options.borrow_mut() = options;
+        self.apply_volumes();
+    }
 
+    fn apply_volumes(&self) {
+        let options = self.options.borrow();
         self.music
             .set_volume(options.master_volume * options.music_volume);
-
-        preferences::save(crate::OPTIONS_STORAGE, &options);
-        *old = options;
+        self.sfx.set_volume(options.master_volume, options.sfx_volumes);
     }
 }
@@ -10,6 +10,16 @@ pub struct MusicManager {
 struct MusicManagerImpl {
     volume: f32,
     playing: Option<Music>,
+    /// Layers looping together, each faded in and out on its own.
+    layers: Vec<MusicLayerState>,
+}
+
+struct MusicLayerState {
+    music: Music,
+    /// Volume relative to the music volume.
+    volume: f32,
+    /// Volume the layer is fading towards.
+    target: f32,
 }
 
 impl MusicManager {
@@ -19,6 +29,7 @@ impl MusicManager {
             inner: RefCell::new(MusicManagerImpl {
                 volume: 0.5,
                 playing: None,
+                layers: Vec::new(),
             }),
         }
     }
@@ -37,11 +48,17 @@ impl MusicManager {
         if let Some(music) = &mut inner.playing {
             music.set_volume(volume);
         }
+        for layer in &mut inner.layers {
+            layer.music.set_volume(layer.volume * volume);
+        }
     }
 
     pub fn set_speed(&self, speed: f32) {
         let mut inner = self.inner.borrow_mut();
-        if let Some(music) = &mut inner.playing {
+        let inner = &mut *inner;
+        // All layers together, so they stay in sync
+        let layers = inner.layers.iter_mut().map(|layer| &mut layer.music);
+        for music in inner.playing.iter_mut().chain(layers) {
             if let Some(effect) = &mut music.effect {
                 effect.set_speed(speed);
             }
@@ -53,6 +70,55 @@ impl MusicManager {
         if let Some(music) = &mut inner.playing {
             music.stop();
         }
+        inner.layers.clear();
+    }
+
+    /// Start the layers from the beginning all at once, silent until faded in.
+    /// Does nothing if the same layers are already playing.
+    pub fn play_layers(&self, layers: &[&Rc<LocalMusic>]) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.layers.len() == layers.len()
+            && inner
+                .layers
+                .iter()
+                .zip(layers)
+                .all(|(layer, &music)| Rc::ptr_eq(&layer.music.local, music))
+        {
+            return;
+        }
+
+        inner.playing = None;
+        inner.layers = layers
+            .iter()
+            .map(|&local| {
+                let mut music = Music::new(self.geng.clone(), local.clone());
+                music.set_volume(0.0);
+                music.play_from(Duration::from_secs_f64(0.0));
+                MusicLayerState {
+                    music,
+                    volume: 0.0,
+                    target: 0.0,
+                }
+            })
+            .collect();
+    }
+
+    /// Set the volume the layer fades towards, from 0 to 1.
+    pub fn fade_layer(&self, index: usize, target: f32) {
+        if let Some(layer) = self.inner.borrow_mut().layers.get_mut(index) {
+            layer.target = target.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Move the layer volumes towards their targets at the given speed per second.
+    pub fn update_layers(&self, delta_time: f32, fade_speed: f32) {
+        let mut inner = self.inner.borrow_mut();
+        let volume = inner.volume;
+        let max_change = fade_speed * delta_time;
+        for layer in &mut inner.layers {
+            layer.volume += (layer.target - layer.volume).clamp(-max_change, max_change);
+            layer.music.set_volume(layer.volume * volume);
+        }
     }
 
     pub fn is_playing(&self) -> Option<Rc<LocalMusic>> {
@@ -95,6 +161,7 @@ impl MusicManager {
         music.set_volume(inner.volume);
         music.play_from(time);
         inner.playing = Some(music);
+        inner.layers.clear();
     }
 }
 
@@ -0,0 +1,98 @@
+use super::*;
+
+/// Sounds of the same kind played closer together than this are skipped.
+const MIN_INTERVAL: f64 = 0.05;
+/// Maximum random change of the playback speed, for some variety.
+const PITCH_VARIATION: f32 = 0.08;
+
+/// Category of a sound effect, each has its own volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SfxCategory {
+    /// Menus and the node editor.
+    Ui,
+    /// The drill and what it runs into.
+    Drill,
+    /// Creatures and hazards underground.
+    Ambient,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SfxVolumes {
+    pub ui: f32,
+    pub drill: f32,
+    pub ambient: f32,
+}
+
+impl Default for SfxVolumes {
+    fn default() -> Self {
+        Self {
+            ui: 1.0,
+            drill: 1.0,
+            ambient: 1.0,
+        }
+    }
+}
+
+impl SfxVolumes {
+    pub fn get(&self, category: SfxCategory) -> f32 {
+        match category {
+            SfxCategory::Ui => self.ui,
+            SfxCategory::Drill => self.drill,
+            SfxCategory::Ambient => self.ambient,
+        }
+    }
+}
+
+pub struct SfxManager {
+    geng: Geng,
+    inner: RefCell<SfxManagerImpl>,
+}
+
+struct SfxManagerImpl {
+    master_volume: f32,
+    volumes: SfxVolumes,
+    timer: Timer,
+    /// When each sound was played last, by the address of the sound.
+    last_played: HashMap<*const geng::Sound, f64>,
+}
+
+impl SfxManager {
+    pub fn new(geng: Geng) -> Self {
+        Self {
+            geng,
+            inner: RefCell::new(SfxManagerImpl {
+                master_volume: 0.5,
+                volumes: SfxVolumes::default(),
+                timer: Timer::new(),
+                last_played: HashMap::new(),
+            }),
+        }
+    }
+
+    pub(super) fn set_volume(&self, master_volume: f32, volumes: SfxVolumes) {
+        let mut inner = self.inner.borrow_mut();
+        inner.master_volume = master_volume;
+        inner.volumes = volumes;
+    }
+
+    pub fn play(&self, sound: &Rc<geng::Sound>, category: SfxCategory) {
+        let mut inner = self.inner.borrow_mut();
+        let volume = inner.master_volume * inner.volumes.get(category);
+        if volume <= 0.0 {
+            return;
+        }
+
+        let time = inner.timer.elapsed().as_secs_f64();
+        let last = inner.last_played.entry(Rc::as_ptr(sound)).or_insert(f64::MIN);
+        if time - *last < MIN_INTERVAL {
+            return;
+        }
+        *last = time;
+
+        let mut effect = sound.effect(self.geng.audio().default_type());
+        effect.set_volume(volume.clamp(0.0, 1.0));
+        effect.set_speed(1.0 + thread_rng().gen_range(-PITCH_VARIATION..=PITCH_VARIATION));
+        effect.play();
+    }
+}
@@ -1,8 +1,14 @@
+mod scenario;
+
+pub use self::scenario::*;
+
 use crate::{
+    menu::{PauseAction, PauseMenu},
     model::*,
     prelude::*,
     render::{
-        mask::MaskedRender,
+        mask::{Light, MaskedRender},
+        post::PostRender,
         util::{TextRenderOptions, UtilRender},
     },
     ui::layout::*,
@@ -10,12 +16,94 @@ use crate::{
 
 use geng_utils::key::EventKey;
 
-#[derive(geng::asset::Load, Serialize, Deserialize, Debug, Clone)]
+#[derive(geng::asset::Load, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[load(serde = "ron")]
 pub struct Controls {
     pub launch: Vec<EventKey>,
     pub left: Vec<EventKey>,
     pub right: Vec<EventKey>,
+    pub recall: Vec<EventKey>,
+    pub stats: Vec<EventKey>,
+    pub screenshot: Vec<EventKey>,
+}
+
+impl Controls {
+    /// The rebindable actions with their names, in the order shown in the menu.
+    pub fn bindings(&self) -> [(&'static str, &Vec<EventKey>); 6] {
+        [
+            ("LAUNCH", &self.launch),
+            ("LEFT", &self.left),
+            ("RIGHT", &self.right),
+            ("RECALL", &self.recall),
+            ("STATS", &self.stats),
+            ("SCREENSHOT", &self.screenshot),
+        ]
+    }
+
+    fn bindings_mut(&mut self) -> [&mut Vec<EventKey>; 6] {
+        [
+            &mut self.launch,
+            &mut self.left,
+            &mut self.right,
+            &mut self.recall,
+            &mut self.stats,
+            &mut self.screenshot,
+        ]
+    }
+
+    /// The controls with the primary keys replaced by the rebinds.
+    pub fn with_rebinds(&self, rebinds: &KeyRebinds) -> Self {
+        let mut controls = self.clone();
+        for (keys, rebind) in controls.bindings_mut().into_iter().zip(rebinds.keys()) {
+            let Some(key) = rebind else { continue };
+            // Replace the primary key, keeping the alternatives
+            match keys.first_mut() {
+                Some(primary) => *primary = key.clone(),
+                None => keys.push(key.clone()),
+            }
+            let mut first = true;
+            keys.retain(|other| other != key || std::mem::take(&mut first));
+        }
+        controls
+    }
+}
+
+/// The player's replacements for the primary key of each action,
+/// stored separately so that new actions do not break the saved options.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct KeyRebinds {
+    pub launch: Option<EventKey>,
+    pub left: Option<EventKey>,
+    pub right: Option<EventKey>,
+    pub recall: Option<EventKey>,
+    pub stats: Option<EventKey>,
+    pub screenshot: Option<EventKey>,
+}
+
+impl KeyRebinds {
+    /// The rebinds in the order of [`Controls::bindings`].
+    pub fn keys(&self) -> [&Option<EventKey>; 6] {
+        [
+            &self.launch,
+            &self.left,
+            &self.right,
+            &self.recall,
+            &self.stats,
+            &self.screenshot,
+        ]
+    }
+
+    pub fn keys_mut(&mut self) -> [&mut Option<EventKey>; 6] {
+        [
+            &mut self.launch,
+            &mut self.left,
+            &mut self.right,
+            &mut self.recall,
+            &mut self.stats,
+            &mut self.screenshot,
+        ]
+    }
 }
 
 pub struct GameState {
@@ -24,6 +112,8 @@ pub struct GameState {
     ui_texture: ugli::Texture,
     game_texture: ugli::Texture,
     mask: MaskedRender,
+    scan_mask: MaskedRender,
+    post: PostRender,
     screen_texture: ugli::Texture,
 
     real_time: FloatTime,
@@ -39,10 +129,27 @@ pub struct GameState {
     screen: Aabb2<f32>,
     ui_view: Aabb2<f32>,
     game_view: Aabb2<f32>,
+    minimap_view: Aabb2<f32>,
 
     show_shop: bool,
     shop_view: Aabb2<f32>,
     shop_items: Vec<Aabb2<f32>>,
+
+    show_stats: bool,
+    /// The options menu, the game is paused while it is open.
+    pause: Option<PauseMenu>,
+    transition: Option<geng::state::Transition>,
+    /// Save the next frame to a file once it is drawn.
+    screenshot_requested: bool,
+    /// Whether the progress is saved for the title screen to continue.
+    save_progress: bool,
+    /// The last biome the drill was seen in, and the banner announcing it.
+    last_biome: Option<BiomeKind>,
+    biome_banner: Option<(BiomeKind, Bounded<FloatTime>)>,
+    /// Whether the hud warned about low fuel on the last frame, reused by the music.
+    low_fuel: bool,
+    /// Music playback speed, eased towards the current intensity.
+    music_speed: f32,
 }
 
 #[derive(Debug)]
@@ -83,26 +190,79 @@ impl GameState {
             screen: Aabb2::ZERO,
             ui_view: Aabb2::ZERO,
             game_view: Aabb2::ZERO,
+            minimap_view: Aabb2::ZERO,
 
             show_shop: false,
             shop_view: Aabb2::ZERO,
             shop_items: Vec::new(),
 
+            show_stats: false,
+            pause: None,
+            transition: None,
+            screenshot_requested: false,
+            save_progress: true,
+            last_biome: None,
+            biome_banner: None,
+            low_fuel: false,
+            music_speed: 1.0,
+
             util: UtilRender::new(context.clone()),
             ui_texture: geng_utils::texture::new_texture(context.geng.ugli(), vec2(1, 1)),
             game_texture: geng_utils::texture::new_texture(context.geng.ugli(), vec2(1, 1)),
             mask: MaskedRender::new(&context.geng, &context.assets, vec2(1, 1)),
+            scan_mask: MaskedRender::new(&context.geng, &context.assets, vec2(1, 1)),
+            post: PostRender::new(&context.geng, &context.assets),
             screen_texture: geng_utils::texture::new_texture(context.geng.ugli(), vec2(1, 1)),
             context,
         }
     }
 
+    pub fn with_seed(context: Context, seed: u64) -> Self {
+        let mut state = Self::new(context);
+        state.model.set_seed(seed);
+        state
+    }
+
+    pub fn from_save(context: Context, save: SaveState) -> Self {
+        let mut state = Self::new(context);
+        state.model.load_save(save);
+        state
+    }
+
+    /// A run with a fixed seed, kept apart from the saved progress.
+    pub fn challenge(context: Context, challenge: &ChallengeConfig) -> Self {
+        let mut state = Self::with_seed(context, challenge.seed);
+        state.model.money = challenge.money;
+        state.save_progress = false;
+        state
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_screenshot(&self, framebuffer: &ugli::Framebuffer) {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let path = run_dir()
+            .join("screenshots")
+            .join(format!("screenshot-{}.png", time));
+        if let Err(err) = crate::render::screenshot::save_framebuffer(framebuffer, &path) {
+            log::error!("Failed to save a screenshot: {:?}", err);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save_screenshot(&self, _framebuffer: &ugli::Framebuffer) {
+        log::warn!("Screenshots are not supported on the web");
+    }
+
     fn layout(&mut self, pixel_scale: f32, framebuffer_size: vec2<usize>) {
         self.screen = Aabb2::ZERO.extend_positive(framebuffer_size.as_f32());
         let padding = 20.0;
         self.game_view = self.screen.extend_uniform(-padding);
         self.ui_view = self.game_view.split_left(0.66).extend_right(-padding / 2.0);
         self.game_view = self.game_view.extend_left(-padding / 2.0);
+        self.minimap_view = self.game_view.cut_right(16.0 * pixel_scale);
+        self.game_view = self.game_view.extend_right(-padding / 2.0);
 
         let shop_size = 4.0 * 50.0 * pixel_scale;
         self.shop_view = self
@@ -119,8 +279,9 @@ impl GameState {
             self.context.geng.ugli(),
         );
         self.mask.update_size(size);
+        self.scan_mask.update_size(size);
 
-        let palette = &self.context.assets.palette;
+        let palette = self.context.palette();
         let sprites = &self.context.assets.sprites;
         let model = &self.model;
 
@@ -131,6 +292,17 @@ impl GameState {
         ugli::clear(framebuffer, Some(palette.background), None, None);
 
         // Background
+        let biome = model
+            .config
+            .biome_at(r32(model.camera.center.y))
+            .map(|biome| biome.kind);
+        let background = match biome {
+            None => &sprites.drill_background_largedot_purple,
+            Some(BiomeKind::Topsoil) => &sprites.background_topsoil,
+            Some(BiomeKind::Bedrock) => &sprites.background_bedrock,
+            Some(BiomeKind::Caverns) => &sprites.background_caverns,
+            Some(BiomeKind::Magma) => &sprites.background_magma,
+        };
         ugli::draw(
             framebuffer,
             &self.context.assets.shaders.tiled_texture,
@@ -138,7 +310,7 @@ impl GameState {
             &self.util.unit_quad,
             (
                 ugli::uniforms! {
-                    u_texture: &*sprites.drill_background_largedot_purple,
+                    u_texture: &**background,
                     u_offset: vec2(0.0, 0.0),
                     u_scale: vec2(framebuffer.size().as_f32().aspect(), 1.0) * 3.0,
                 },
@@ -147,47 +319,257 @@ impl GameState {
             ugli::DrawParameters::default(),
         );
 
-        // Vision mask
-        let mut mask = self.mask.start();
-        self.context.geng.draw2d().circle(
-            &mut mask.mask,
-            &model.camera,
-            model.drill.collider.position.as_f32(),
-            model.drill.vision_radius.as_f32(),
-            Color::WHITE,
-        );
+        // Tunnels outside of vision stay faintly visible
+        let tunnel_width = model.config.drill_size.as_f32();
+        let tunnels: Vec<Vec<vec2<f32>>> = model
+            .tunnels
+            .iter()
+            .filter(|tunnel| tunnel.points.len() >= 2)
+            .map(|tunnel| tunnel.points.iter().map(|p| p.as_f32()).collect())
+            .collect();
+        for points in &tunnels {
+            self.util.draw_chain(
+                framebuffer,
+                &model.camera,
+                &draw2d::Chain::new(
+                    Chain::new(points.clone()),
+                    tunnel_width,
+                    palette.tunnel_explored,
+                    3,
+                ),
+            );
+        }
 
-        // Minerals
+        // Minerals remembered from before
+        let brightness = 1.0 - model.config.remembered_dim;
         for mineral in &model.minerals {
-            let color = match mineral.kind {
-                MineralKind::Resource(kind) => {
-                    let texture = match kind {
-                        ResourceKind::Coal => &sprites.coal_ore,
-                        ResourceKind::Iron => &sprites.iron_ore,
-                        ResourceKind::Bronze => &sprites.bronze_ore,
-                        ResourceKind::Silver => &sprites.silver_ore,
-                        ResourceKind::Gold => &sprites.gold_ore,
-                        ResourceKind::Gem => continue, // &sprites.gem,
-                    };
-                    self.util.draw_texture_pp(
-                        texture,
-                        mineral.collider.position.as_f32(),
-                        vec2(0.5, 0.5),
-                        mineral.collider.rotation.as_f32(),
-                        1.0,
-                        Color::WHITE,
-                        &model.camera,
-                        &mut mask.color,
-                    );
+            if model.is_explored(mineral.collider.position) {
+                draw_mineral(
+                    &self.util,
+                    &self.context.assets,
+                    model,
+                    mineral,
+                    brightness,
+                    framebuffer,
+                );
+            }
+        }
+
+        // Minerals revealed by the scanner ping, as silhouettes
+        if let Some(ping) = &model.scanner_ping {
+            let alpha = ping.alpha().as_f32();
+            let mut scan = self.scan_mask.start();
+            self.context.geng.draw2d().circle(
+                &mut scan.mask,
+                &model.camera,
+                ping.center.as_f32(),
+                ping.radius().as_f32(),
+                Color::WHITE,
+            );
+            for mineral in &model.minerals {
+                let MineralKind::Resource(kind) = mineral.kind else {
+                    continue;
+                };
+                if !ping.reveals(mineral.kind) {
                     continue;
                 }
-                MineralKind::Rock => palette.rock,
+                let color = palette
+                    .resources
+                    .get(&kind)
+                    .copied()
+                    .unwrap_or(palette.default);
+                self.util.draw_collider(
+                    &mineral.collider,
+                    Color { a: color.a * alpha, ..color },
+                    &model.camera,
+                    &mut scan.color,
+                );
+            }
+            self.scan_mask.draw(
+                ugli::DrawParameters {
+                    blend_mode: Some(ugli::BlendMode::straight_alpha()),
+                    ..default()
+                },
+                framebuffer,
+            );
+            let radius = ping.radius().as_f32();
+            self.context.geng.draw2d().circle_with_cut(
+                framebuffer,
+                &model.camera,
+                ping.center.as_f32(),
+                radius - 0.1,
+                radius,
+                Color {
+                    a: palette.scanner.a * alpha,
+                    ..palette.scanner
+                },
+            );
+        }
+
+        // Light map: the drill lamp and glow, glowing ore and lava
+        let mut mask = self.mask.start();
+        let drill_position = model.drill.collider.position.as_f32();
+        let mut lights = vec![
+            Light {
+                position: drill_position,
+                radius: model.drill.vision_radius.as_f32(),
+                intensity: 1.5,
+                cone: Some((
+                    model.drill.collider.rotation.as_f32(),
+                    model.drill.vision_cone.as_f32(),
+                )),
+            },
+            Light {
+                position: drill_position,
+                radius: model.config.drill_glow.as_f32(),
+                intensity: 1.5,
+                cone: None,
+            },
+        ];
+        for mineral in &model.minerals {
+            let glow = model
+                .config
+                .minerals
+                .get(&mineral.kind)
+                .map_or(0.0, |config| config.glow.as_f32());
+            if glow > 0.0 {
+                lights.push(Light {
+                    position: mineral.collider.position.as_f32(),
+                    radius: glow,
+                    intensity: 0.8,
+                    cone: None,
+                });
+            }
+        }
+        for hazard in &model.hazards {
+            let (HazardKind::Lava, Shape::Circle { radius }) = (hazard.kind, hazard.collider.shape)
+            else {
+                continue;
             };
+            lights.push(Light {
+                position: hazard.collider.position.as_f32(),
+                radius: radius.as_f32() * 1.5,
+                intensity: 0.8,
+                cone: None,
+            });
+        }
+        let view_distance = model.camera.fov.value();
+        for light in lights {
+            if (light.position - model.camera.center).len() - light.radius < view_distance {
+                mask.light(&light, &model.camera);
+            }
+        }
+
+        // Caves: walls first, so that overlapping parts merge into one area
+        let cave_wall = 0.1;
+        for (color, shrink) in [(palette.cave_edge, 0.0), (palette.cave, cave_wall)] {
+            for part in model.caves.iter().flat_map(|cave| &cave.parts) {
+                let Shape::Circle { radius } = part.shape else {
+                    continue;
+                };
+                self.context.geng.draw2d().circle(
+                    &mut mask.color,
+                    &model.camera,
+                    part.position.as_f32(),
+                    radius.as_f32() - shrink,
+                    color,
+                );
+            }
+        }
+
+        // Tunnels
+        for points in tunnels {
+            self.util.draw_chain(
+                &mut mask.color,
+                &model.camera,
+                &draw2d::Chain::new(Chain::new(points), tunnel_width, palette.tunnel, 3),
+            );
+        }
+
+        // Hazards
+        for hazard in &model.hazards {
+            let color = palette
+                .hazards
+                .get(&hazard.kind)
+                .copied()
+                .unwrap_or(palette.default);
             self.util
-                .draw_collider(&mineral.collider, color, &model.camera, &mut mask.color);
+                .draw_collider(&hazard.collider, color, &model.camera, &mut mask.color);
         }
 
-        self.mask.draw(ugli::DrawParameters::default(), framebuffer);
+        // Minerals
+        for mineral in &model.minerals {
+            draw_mineral(&self.util, &self.context.assets, model, mineral, 1.0, &mut mask.color);
+        }
+
+        // Pickups
+        for pickup in &model.pickups {
+            self.util.draw_texture_pp(
+                &sprites.chest,
+                pickup.collider.position.as_f32(),
+                vec2(0.5, 0.5),
+                Angle::ZERO,
+                1.0,
+                Color::WHITE,
+                &model.camera,
+                &mut mask.color,
+            );
+        }
+
+        // Creatures
+        for (kind, position, velocity, radius) in query!(
+            model.creatures,
+            (&kind, &position, &velocity, &radius)
+        ) {
+            let color = palette
+                .creatures
+                .get(kind)
+                .copied()
+                .unwrap_or(palette.default);
+            let segments = match kind {
+                CreatureKind::Worm => 4,
+                CreatureKind::Pest => 1,
+            };
+            // Body segments trail behind the head
+            let back = -velocity.normalize_or_zero().as_f32() * radius.as_f32();
+            for i in (0..segments).rev() {
+                let t = i as f32;
+                self.context.geng.draw2d().circle(
+                    &mut mask.color,
+                    &model.camera,
+                    position.as_f32() + back * t,
+                    radius.as_f32() * (1.0 - t * 0.15),
+                    color,
+                );
+            }
+        }
+
+        // Sell points
+        for point in &model.sell_points {
+            let color = if point.used {
+                Color::WHITE.map_rgb(|x| x * 0.5)
+            } else {
+                Color::WHITE
+            };
+            self.util.draw_texture_pp(
+                &sprites.coin,
+                point.collider.position.as_f32(),
+                vec2(0.5, 0.5),
+                Angle::ZERO,
+                3.0,
+                color,
+                &model.camera,
+                &mut mask.color,
+            );
+        }
+
+        self.mask.draw(
+            ugli::DrawParameters {
+                blend_mode: Some(ugli::BlendMode::straight_alpha()),
+                ..default()
+            },
+            framebuffer,
+        );
 
         // Level bounds
         self.context.geng.draw2d().draw2d(
@@ -227,14 +609,61 @@ impl GameState {
             framebuffer,
         );
 
-        // Drill vision
-        self.context.geng.draw2d().circle_with_cut(
+        // Particles
+        for (kind, position, radius, size_function, lifetime) in query!(
+            self.model.particles,
+            (&kind, &position, &radius, &size_function, &lifetime)
+        ) {
+            let color = match kind {
+                ParticleKind::WagonDamaged | ParticleKind::WagonDestroyed => palette.damage,
+                ParticleKind::WagonHealing => palette.healing,
+                ParticleKind::Collect(kind) => palette
+                    .resources
+                    .get(kind)
+                    .copied()
+                    .unwrap_or(palette.default),
+                ParticleKind::Pickup => palette.drill,
+                ParticleKind::Hazard(kind) => palette
+                    .hazards
+                    .get(kind)
+                    .copied()
+                    .unwrap_or(palette.default),
+                ParticleKind::Creature(kind) => palette
+                    .creatures
+                    .get(kind)
+                    .copied()
+                    .unwrap_or(palette.default),
+                ParticleKind::Zap => palette.zap,
+                _ => palette.default,
+            };
+            let t = lifetime.get_ratio().as_f32();
+            let scale = match size_function {
+                SizeFunction::Shrink => t,
+                SizeFunction::GrowShrink => {
+                    let x = 2.0 * t - 1.0;
+                    1.0 - x * x
+                }
+            };
+            self.context.geng.draw2d().circle(
+                framebuffer,
+                &model.camera,
+                position.as_f32(),
+                radius.as_f32() * scale,
+                color,
+            );
+        }
+
+        // Drill lamp reach
+        let rotation = model.drill.collider.rotation.as_f32();
+        let cone = model.drill.vision_cone.as_f32();
+        self.util.draw_circle_arc(
             framebuffer,
             &model.camera,
-            model.drill.collider.position.as_f32(),
-            model.drill.vision_radius.as_f32() * 0.97,
-            model.drill.vision_radius.as_f32(),
+            mat3::translate(model.drill.collider.position.as_f32())
+                * mat3::scale_uniform(model.drill.vision_radius.as_f32()),
             palette.vision_circle,
+            0.97,
+            rotation - cone..=rotation + cone,
         );
 
         // Floating Text
@@ -261,7 +690,7 @@ impl GameState {
         );
 
         let font_size = 25.0 * pixel_scale;
-        let palette = &self.context.assets.palette;
+        let palette = self.context.palette();
         let sprites = &self.context.assets.sprites;
 
         // Depth meter
@@ -310,6 +739,323 @@ impl GameState {
             &geng::PixelPerfectCamera,
             framebuffer,
         );
+
+        // Hud
+        let config = &self.context.assets.config;
+        let hud_font_size = 12.0 * pixel_scale;
+        let padding = 5.0 * pixel_scale;
+        let bar_height = 4.0 * pixel_scale;
+        let label_width = hud_font_size * 3.5;
+        let blink = (self.real_time * config.blink_frequency).floor().as_f32() as i64 % 2 == 0;
+
+        let mut hud = self.game_view.extend_uniform(-2.0 * padding);
+        let hud = hud
+            .cut_bottom(hud_font_size * 8.0 + padding * 2.0)
+            .with_width(label_width * 2.5, 0.0);
+        self.util.draw_nine_slice(
+            hud,
+            palette.background,
+            &sprites.fill_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        self.util.draw_nine_slice(
+            hud,
+            palette.game_view,
+            &sprites.border_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        let mut rows = hud.extend_uniform(-padding);
+
+        // Fuel gauge
+        let fuel = self.model.connected_fuel();
+        let low_fuel =
+            matches!(self.model.phase, Phase::Drill) && fuel.value() < config.low_fuel_warning;
+        self.low_fuel = low_fuel;
+        let fuel_ratio = if fuel.max() > Fuel::ZERO {
+            fuel.get_ratio().as_f32()
+        } else {
+            0.0
+        };
+        let (fuel_back, fuel_front) = if low_fuel && blink {
+            (palette.battery_critical, palette.battery_critical)
+        } else {
+            (palette.fuel_back, palette.fuel_front)
+        };
+
+        // Sprint: remaining duration while active, charge otherwise
+        let sprint_ratio = if let Some(sprint) = &self.model.drill.sprint {
+            Some(sprint.duration.get_ratio().as_f32())
+        } else {
+            self.model.nodes.nodes.iter().find_map(|node| {
+                if let NodeKind::Sprint { cooldown } = &node.kind {
+                    Some(1.0 - cooldown.get_ratio().as_f32())
+                } else {
+                    None
+                }
+            })
+        };
+
+        let bars = [
+            ("FUEL", Some(fuel_ratio), fuel_back, fuel_front),
+            (
+                "HULL",
+                Some(self.model.drill.hull.get_ratio().as_f32()),
+                palette.hull_back,
+                palette.hull_front,
+            ),
+            ("SPRINT", sprint_ratio, palette.sprint_back, palette.sprint_front),
+            (
+                "TIME",
+                Some((1.0 - (self.model.run.duration / config.max_run_time).as_f32()).max(0.0)),
+                palette.time_back,
+                palette.time_front,
+            ),
+        ];
+        for (label, ratio, back, front) in bars {
+            let mut row = rows.cut_top(hud_font_size);
+            let label_pos = row.cut_left(label_width);
+            self.util.draw_text(
+                label,
+                label_pos.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(hud_font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.game_view),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            let mut bar = row.with_height(bar_height, 0.5);
+            self.util.draw_quad_outline(
+                bar,
+                pixel_scale,
+                back,
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            if let Some(ratio) = ratio {
+                self.context.geng.draw2d().quad(
+                    framebuffer,
+                    &geng::PixelPerfectCamera,
+                    bar.split_left(ratio),
+                    front,
+                );
+            }
+        }
+
+        // Cargo hold, stacked by resource
+        {
+            let mut row = rows.cut_top(hud_font_size);
+            let label_pos = row.cut_left(label_width);
+            self.util.draw_text(
+                "CARGO",
+                label_pos.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(hud_font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.game_view),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            let cargo = &self.model.cargo;
+            self.util.draw_stacked_bar(
+                row.with_height(bar_height, 0.5),
+                pixel_scale,
+                palette.cargo_back,
+                cargo_segments(cargo, palette),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+        }
+
+        // Drill stats
+        let stats = [
+            (
+                "TIER",
+                format!("{:?}", self.model.drill.drill_level).to_uppercase(),
+            ),
+            ("SPEED", format!("{:.1}", self.model.drill.speed.as_f32())),
+            (
+                "VISION",
+                format!("{:.1}", self.model.drill.vision_radius.as_f32()),
+            ),
+        ];
+        for (label, value) in stats {
+            let mut row = rows.cut_top(hud_font_size);
+            let label_pos = row.cut_left(label_width);
+            self.util.draw_text(
+                label,
+                label_pos.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(hud_font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.game_view),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            self.util.draw_text(
+                value,
+                row.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(hud_font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.depth_text),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+        }
+
+        // Biome banner
+        if let Some((biome, timer)) = &self.biome_banner {
+            let color = palette
+                .biomes
+                .get(biome)
+                .map_or(palette.default, |biome| biome.banner);
+            let t = timer.get_ratio().as_f32();
+            self.util.draw_text(
+                format!("{:?}", biome).to_uppercase(),
+                self.game_view.align_pos(vec2(0.5, 0.65)),
+                &self.context.assets.fonts.revolver_display,
+                TextRenderOptions::new(font_size * 1.5).color(Color {
+                    a: (t * 3.0).min(1.0),
+                    ..color
+                }),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+        }
+
+        // Low fuel warning
+        if low_fuel && blink {
+            self.util.draw_text(
+                "LOW FUEL",
+                self.game_view.align_pos(vec2(0.5, 0.8)),
+                &self.context.assets.fonts.revolver_display,
+                TextRenderOptions::new(font_size).color(palette.battery_critical),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+        }
+    }
+
+    fn draw_minimap(&mut self, pixel_scale: f32) {
+        let framebuffer = &mut geng_utils::texture::attach_texture(
+            &mut self.screen_texture,
+            self.context.geng.ugli(),
+        );
+
+        let palette = self.context.palette();
+        let sprites = &self.context.assets.sprites;
+        let model = &self.model;
+
+        let view = self.minimap_view;
+        self.util.draw_nine_slice(
+            view,
+            palette.background,
+            &sprites.fill_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        self.util.draw_nine_slice(
+            view,
+            palette.game_view,
+            &sprites.border_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        let area = view.extend_uniform(-3.0 * pixel_scale);
+
+        // From the surface down past the deepest point of interest
+        let best_depth = self.context.best_depth();
+        let top = 0.0;
+        let bottom = [
+            model.drill.collider.position.y,
+            -model.run.max_depth,
+            -best_depth.unwrap_or(Coord::ZERO),
+        ]
+        .into_iter()
+        .min()
+        .unwrap_or(Coord::ZERO)
+        .as_f32()
+            - 10.0;
+        let bounds = model.bounds.as_f32();
+        let to_screen = |pos: vec2<f32>| {
+            let t = vec2(
+                (pos.x - bounds.min.x) / bounds.width(),
+                (pos.y - bottom) / (top - bottom),
+            );
+            area.bottom_left() + area.size() * t.map(|x| x.clamp(0.0, 1.0))
+        };
+
+        // Explored cells, neighbours in a row merged into a single quad
+        let mut rows: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for &(x, y) in &model.explored {
+            rows.entry(y).or_default().push(x);
+        }
+        let cell = model.config.explored_cell_size.as_f32();
+        for (y, mut xs) in rows {
+            xs.sort_unstable();
+            let mut spans: Vec<(i64, i64)> = Vec::new();
+            for x in xs {
+                match spans.last_mut() {
+                    Some((_, end)) if *end + 1 == x => *end = x,
+                    _ => spans.push((x, x)),
+                }
+            }
+            for (min_x, max_x) in spans {
+                let min = vec2(min_x as f32, y as f32) * cell;
+                let max = vec2(max_x as f32 + 1.0, y as f32 + 1.0) * cell;
+                self.context.geng.draw2d().quad(
+                    framebuffer,
+                    &geng::PixelPerfectCamera,
+                    Aabb2::from_corners(to_screen(min), to_screen(max)),
+                    palette.minimap_explored,
+                );
+            }
+        }
+
+        // Ore seen
+        for mineral in &model.minerals {
+            let MineralKind::Resource(kind) = mineral.kind else {
+                continue;
+            };
+            if !model.is_explored(mineral.collider.position) {
+                continue;
+            }
+            let color = palette
+                .resources
+                .get(&kind)
+                .copied()
+                .unwrap_or(palette.default);
+            self.context.geng.draw2d().quad(
+                framebuffer,
+                &geng::PixelPerfectCamera,
+                Aabb2::point(to_screen(mineral.collider.position.as_f32()))
+                    .extend_uniform(pixel_scale),
+                color,
+            );
+        }
+
+        // Best depth from the previous runs and the current one
+        let mut markers = vec![(model.drill.collider.position.y, palette.drill)];
+        if let Some(depth) = best_depth {
+            markers.insert(0, (-depth, palette.depth_text));
+        }
+        for (y, color) in markers {
+            let y = to_screen(vec2(0.0, y.as_f32())).y;
+            self.context.geng.draw2d().quad(
+                framebuffer,
+                &geng::PixelPerfectCamera,
+                Aabb2::from_corners(vec2(view.min.x, y), vec2(view.max.x, y))
+                    .extend_symmetric(vec2(0.0, pixel_scale / 2.0)),
+                color,
+            );
+        }
     }
 
     fn draw_nodes(&mut self, pixel_scale: f32) {
@@ -326,7 +1072,7 @@ impl GameState {
         );
 
         let nodes = &mut self.model.nodes;
-        let palette = &self.context.assets.palette;
+        let palette = self.context.palette();
         let sprites = &self.context.assets.sprites;
 
         ugli::clear(framebuffer, Some(palette.background), None, None);
@@ -389,12 +1135,25 @@ impl GameState {
                     _ => &sprites.drill_2_speed,
                 },
                 NodeKind::CoalFuel(..) => &sprites.coal_fuel_node,
+                NodeKind::Armor { .. } => &sprites.armor_node,
+                NodeKind::Repair(..) => &sprites.repair_node,
+                NodeKind::Cargo => &sprites.cargo_node,
+                NodeKind::CargoExpansion => &sprites.cargo_expansion_node,
+                NodeKind::Winch => &sprites.winch_node,
+                NodeKind::Elevator { .. } => &sprites.elevator_node,
+                NodeKind::Filter => &sprites.filter_node,
+                NodeKind::Cooling => &sprites.cooling_node,
+                NodeKind::Pump => &sprites.pump_node,
+                NodeKind::Zapper { .. } => &sprites.zapper_node,
+                NodeKind::Scanner { .. } => &sprites.scanner_node,
+                NodeKind::Tuner { .. } => &sprites.tuner_node,
             };
             let position = node.position.map_bounds(to_screen);
 
             // Color
             let mut color = Color::WHITE;
-            if !node.is_powered && !matches!(node.kind, NodeKind::Upgrade) {
+            let is_passive = matches!(node.kind, NodeKind::Upgrade | NodeKind::Tuner { .. });
+            if !node.is_powered && !is_passive {
                 color =
                     color.map_rgb(|x| x * (1.0 - self.context.assets.config.unpowered_node_dim));
             }
@@ -705,6 +1464,131 @@ impl GameState {
                 NodeKind::Battery => {}
                 NodeKind::Vision { .. } => {}
                 NodeKind::Speed { .. } => {}
+                NodeKind::Armor { .. } => {}
+                NodeKind::Repair(charge) => {
+                    let pos = node
+                        .position
+                        .as_f32()
+                        .extend_uniform(-0.1)
+                        .extend_up(-0.075)
+                        .as_r32();
+                    let mut pos = Aabb2::from_corners(to_screen(pos.min), to_screen(pos.max))
+                        .with_height(pixel_scale * 4.0, 1.0);
+                    self.util.draw_quad_outline(
+                        pos,
+                        pixel_scale,
+                        palette.hull_back,
+                        &geng::PixelPerfectCamera,
+                        framebuffer,
+                    );
+                    self.context.geng.draw2d().quad(
+                        framebuffer,
+                        &geng::PixelPerfectCamera,
+                        pos.split_left(charge.get_ratio().as_f32()),
+                        palette.hull_front,
+                    );
+                }
+                NodeKind::Cargo => {
+                    let area = Aabb2::from_corners(
+                        position.align_pos(vec2(0.33, 0.15)),
+                        position.align_pos(vec2(0.94, 0.7)),
+                    );
+                    let cargo = &self.model.cargo;
+                    self.util.draw_stacked_bar(
+                        area.with_height(pixel_scale * 4.0, 1.0),
+                        pixel_scale,
+                        palette.cargo_back,
+                        cargo_segments(cargo, palette),
+                        &geng::PixelPerfectCamera,
+                        framebuffer,
+                    );
+                    self.util.draw_text(
+                        format!("{}/{}", cargo.total(), cargo.capacity),
+                        area.align_pos(vec2(0.5, 0.3)),
+                        &self.context.assets.fonts.revolver_game,
+                        TextRenderOptions::new(pixel_scale * 8.0).color(palette.gold_text),
+                        &geng::PixelPerfectCamera,
+                        framebuffer,
+                    );
+                }
+                NodeKind::CargoExpansion => {}
+                NodeKind::Winch => {}
+                NodeKind::Filter | NodeKind::Cooling | NodeKind::Pump => {}
+                NodeKind::Tuner { resource } => {
+                    let color = palette.resources.get(resource).copied().unwrap_or(palette.default);
+                    self.util.draw_text(
+                        format!("{:?}", resource).to_uppercase(),
+                        position.align_pos(vec2(0.5, 0.5)),
+                        &self.context.assets.fonts.revolver_game,
+                        TextRenderOptions::new(pixel_scale * 8.0).color(color),
+                        &geng::PixelPerfectCamera,
+                        framebuffer,
+                    );
+                }
+                NodeKind::Zapper { cooldown } | NodeKind::Scanner { cooldown, .. } => {
+                    let color = if let NodeKind::Scanner { filter, .. } = &node.kind {
+                        // Kind of ore being scanned for
+                        let (text, color) = match filter {
+                            None => ("ALL".to_owned(), palette.scanner),
+                            Some(kind) => (
+                                format!("{:?}", kind).to_uppercase(),
+                                palette
+                                    .resources
+                                    .get(kind)
+                                    .copied()
+                                    .unwrap_or(palette.default),
+                            ),
+                        };
+                        self.util.draw_text(
+                            text,
+                            position.align_pos(vec2(0.5, 0.2)),
+                            &self.context.assets.fonts.revolver_game,
+                            TextRenderOptions::new(pixel_scale * 8.0).color(color),
+                            &geng::PixelPerfectCamera,
+                            framebuffer,
+                        );
+                        palette.scanner
+                    } else {
+                        palette.zap
+                    };
+
+                    // Recharge
+                    let pos = node
+                        .position
+                        .as_f32()
+                        .extend_uniform(-0.1)
+                        .extend_down(0.025)
+                        .as_r32();
+                    let mut pos = Aabb2::from_corners(to_screen(pos.min), to_screen(pos.max))
+                        .with_height(pixel_scale * 4.0, 0.0);
+                    self.util.draw_quad_outline(
+                        pos,
+                        pixel_scale,
+                        palette.sprint_back,
+                        &geng::PixelPerfectCamera,
+                        framebuffer,
+                    );
+                    self.context.geng.draw2d().quad(
+                        framebuffer,
+                        &geng::PixelPerfectCamera,
+                        pos.split_left(1.0 - cooldown.get_ratio().as_f32()),
+                        color,
+                    );
+                }
+                &NodeKind::Elevator { checkpoint } => {
+                    let depth = checkpoint
+                        .checked_sub(1)
+                        .and_then(|i| self.model.config.checkpoints.get(i))
+                        .map_or(0, |depth| depth.as_f32().round() as i64);
+                    self.util.draw_text(
+                        format!("{}", depth),
+                        position.align_pos(vec2(0.5, 0.2)),
+                        &self.context.assets.fonts.revolver_game,
+                        TextRenderOptions::new(pixel_scale * 8.0).color(palette.depth_text),
+                        &geng::PixelPerfectCamera,
+                        framebuffer,
+                    );
+                }
             }
         }
     }
@@ -719,7 +1603,7 @@ impl GameState {
             self.context.geng.ugli(),
         );
 
-        let palette = &self.context.assets.palette;
+        let palette = self.context.palette();
         let sprites = &self.context.assets.sprites;
 
         self.util.draw_nine_slice(
@@ -757,6 +1641,17 @@ impl GameState {
                 ShopNode::Light => &sprites.drill_0_light,
                 ShopNode::Sprint => &sprites.sprint_button_normal,
                 ShopNode::CoalFuel => &sprites.coal_fuel_node,
+                ShopNode::Armor => &sprites.armor_node,
+                ShopNode::Repair => &sprites.repair_node,
+                ShopNode::CargoExpansion => &sprites.cargo_expansion_node,
+                ShopNode::Winch => &sprites.winch_node,
+                ShopNode::Elevator => &sprites.elevator_node,
+                ShopNode::Filter => &sprites.filter_node,
+                ShopNode::Cooling => &sprites.cooling_node,
+                ShopNode::Pump => &sprites.pump_node,
+                ShopNode::Zapper => &sprites.zapper_node,
+                ShopNode::Scanner => &sprites.scanner_node,
+                ShopNode::Tuner(_) => &sprites.tuner_node,
             };
             let size = texture.size().as_f32() * pixel_scale;
             row_height = row_height.max(size.y + cost_height);
@@ -800,8 +1695,9 @@ impl GameState {
                 &geng::PixelPerfectCamera,
                 framebuffer,
             );
+            let cost = self.model.item_cost(&item.item);
             self.util.draw_text(
-                format!("{}", item.item.cost),
+                format!("{}", cost),
                 position.align_pos(vec2(0.0, 0.5)),
                 &self.context.assets.fonts.revolver_game,
                 TextRenderOptions::new(coin.size().y as f32 * pixel_scale)
@@ -813,11 +1709,279 @@ impl GameState {
         }
     }
 
+    fn draw_summary(&mut self, pixel_scale: f32) {
+        let Phase::Summary { reason } = self.model.phase else {
+            return;
+        };
+
+        let framebuffer = &mut geng_utils::texture::attach_texture(
+            &mut self.screen_texture,
+            self.context.geng.ugli(),
+        );
+
+        let palette = self.context.palette();
+        let sprites = &self.context.assets.sprites;
+        let run = &self.model.run;
+
+        let font_size = 12.0 * pixel_scale;
+        let padding = 5.0 * pixel_scale;
+        let label_width = font_size * 5.0;
+
+        let mut rows = vec![
+            ("DEPTH", format!("{}", run.max_depth.as_f32().ceil() as i64)),
+            ("TIME", format!("{:.0}s", run.duration.as_f32())),
+            ("FUEL USED", format!("{:.1}", run.fuel_used.as_f32())),
+        ];
+        for (kind, count) in &run.ore {
+            rows.push(("", format!("{:?} x{}", kind, count).to_uppercase()));
+        }
+        rows.push(("EARNED", format!("{}", run.money)));
+
+        let title = match reason {
+            RunEnd::OutOfFuel => "OUT OF FUEL",
+            RunEnd::Destroyed => "DRILL DESTROYED",
+            RunEnd::Recalled => "RECALLED",
+            RunEnd::TimeOut => "TIME IS UP",
+        };
+
+        let height = font_size * (rows.len() as f32 + 4.0) + padding * 2.0;
+        let panel = self
+            .game_view
+            .with_width(label_width * 2.5, 0.5)
+            .with_height(height, 0.5);
+        self.util.draw_nine_slice(
+            panel,
+            palette.background,
+            &sprites.fill_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        self.util.draw_nine_slice(
+            panel,
+            palette.game_view,
+            &sprites.border_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        let mut panel = panel.extend_uniform(-padding);
+
+        let title_pos = panel.cut_top(font_size * 2.0);
+        self.util.draw_text(
+            title,
+            title_pos.center(),
+            &self.context.assets.fonts.revolver_display,
+            TextRenderOptions::new(font_size * 1.5).color(palette.depth_text),
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+
+        for (label, value) in rows {
+            let mut row = panel.cut_top(font_size);
+            let label_pos = row.cut_left(label_width);
+            self.util.draw_text(
+                label,
+                label_pos.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.game_view),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            self.util.draw_text(
+                value,
+                row.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.gold_text),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+        }
+
+        self.util.draw_text(
+            "CLICK TO CONTINUE",
+            panel.align_pos(vec2(0.5, 0.0)),
+            &self.context.assets.fonts.revolver_game,
+            TextRenderOptions::new(font_size)
+                .align(vec2(0.5, 0.0))
+                .color(palette.game_view),
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+    }
+
+    fn draw_stats(&mut self, pixel_scale: f32) {
+        if !self.show_stats {
+            return;
+        }
+
+        let framebuffer = &mut geng_utils::texture::attach_texture(
+            &mut self.screen_texture,
+            self.context.geng.ugli(),
+        );
+
+        let palette = self.context.palette();
+        let sprites = &self.context.assets.sprites;
+        let config = &self.context.assets.config;
+        let history = self.context.history();
+
+        let font_size = 12.0 * pixel_scale;
+        let padding = 5.0 * pixel_scale;
+        let label_width = font_size * 6.0;
+
+        let panel = self
+            .game_view
+            .with_width(label_width * 2.0, 0.5)
+            .with_height(font_size * 14.0 + padding * 2.0, 0.5);
+        self.util.draw_nine_slice(
+            panel,
+            palette.background,
+            &sprites.fill_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        self.util.draw_nine_slice(
+            panel,
+            palette.game_view,
+            &sprites.border_thinner,
+            pixel_scale,
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        let mut panel = panel.extend_uniform(-padding);
+
+        let title_pos = panel.cut_top(font_size * 2.0);
+        self.util.draw_text(
+            "RECORDS",
+            title_pos.center(),
+            &self.context.assets.fonts.revolver_display,
+            TextRenderOptions::new(font_size * 1.5).color(palette.depth_text),
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+
+        let (Some(best_depth), Some(best_haul)) = (history.best_depth(), history.best_haul())
+        else {
+            self.util.draw_text(
+                "NO RUNS YET",
+                panel.center(),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(font_size).color(palette.game_view),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            return;
+        };
+
+        let recent = history.last(config.history_trend_runs);
+        let count = recent.len().max(1) as f32;
+        let average_depth = recent
+            .iter()
+            .map(|run| run.stats.max_depth.as_f32())
+            .sum::<f32>()
+            / count;
+        let average_haul = recent
+            .iter()
+            .map(|run| run.stats.money as f32)
+            .sum::<f32>()
+            / count;
+
+        let rows = [
+            ("RUNS", format!("{}", history.total_runs())),
+            ("BEST DEPTH", format!("{}", best_depth.as_f32().ceil() as i64)),
+            ("BEST HAUL", format!("{}", best_haul)),
+            ("AVG DEPTH", format!("{:.0}", average_depth)),
+            ("AVG HAUL", format!("{:.0}", average_haul)),
+        ];
+        for (label, value) in rows {
+            let mut row = panel.cut_top(font_size);
+            let label_pos = row.cut_left(label_width);
+            self.util.draw_text(
+                label,
+                label_pos.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.game_view),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+            self.util.draw_text(
+                value,
+                row.align_pos(vec2(0.0, 0.5)),
+                &self.context.assets.fonts.revolver_game,
+                TextRenderOptions::new(font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(palette.gold_text),
+                &geng::PixelPerfectCamera,
+                framebuffer,
+            );
+        }
+
+        // Trends: depth and haul of the recent runs relative to the best ones
+        let label_pos = panel.cut_top(font_size * 1.5);
+        self.util.draw_text(
+            format!("LAST {} RUNS", recent.len()),
+            label_pos.align_pos(vec2(0.0, 0.5)),
+            &self.context.assets.fonts.revolver_game,
+            TextRenderOptions::new(font_size)
+                .align(vec2(0.0, 0.5))
+                .color(palette.game_view),
+            &geng::PixelPerfectCamera,
+            framebuffer,
+        );
+        let chart = panel;
+        let column_width = chart.width() / config.history_trend_runs.max(1) as f32;
+        for (i, run) in recent.iter().enumerate() {
+            let mut column =
+                Aabb2::point(chart.bottom_left() + vec2(column_width * i as f32, 0.0))
+                    .extend_right(column_width - pixel_scale)
+                    .extend_up(chart.height());
+            let depth_column = column.split_left(0.5);
+            let depth = (run.stats.max_depth / best_depth.max(Coord::ONE)).as_f32();
+            let haul = run.stats.money as f32 / best_haul.max(1) as f32;
+            self.context.geng.draw2d().quad(
+                framebuffer,
+                &geng::PixelPerfectCamera,
+                depth_column.with_height(depth_column.height() * depth, 0.0),
+                palette.depth_text,
+            );
+            self.context.geng.draw2d().quad(
+                framebuffer,
+                &geng::PixelPerfectCamera,
+                column.with_height(column.height() * haul, 0.0),
+                palette.gold_text,
+            );
+        }
+    }
+
+    /// Back to the base after a run, a good moment to save the progress.
+    fn dismiss_summary(&mut self) {
+        self.model.dismiss_summary();
+        self.store_save();
+    }
+
+    fn store_save(&self) {
+        if self.save_progress {
+            self.context.store_save(&self.model.save());
+        }
+    }
+
     fn toggle_shop(&mut self) {
         self.show_shop = !self.show_shop;
     }
 
     fn mouse_down(&mut self) {
+        if let Phase::Summary { .. } = self.model.phase {
+            self.dismiss_summary();
+            return;
+        }
+
         self.end_drag();
         if let Some(target) = self.hovering.clone() {
             self.start_drag(target);
@@ -847,6 +2011,14 @@ impl GameState {
                         // We can still drag the node - start sprinting
                         self.model.start_sprint(index);
                     }
+                    NodeKind::Winch => {
+                        // We can still drag the node - recall the drill
+                        self.model.recall_drill(index);
+                    }
+                    NodeKind::Elevator { .. } => {
+                        // We can still drag the node - pick the starting depth
+                        self.model.cycle_elevator(index);
+                    }
                     _ => (),
                 }
             }
@@ -898,7 +2070,7 @@ impl GameState {
         };
         log::debug!("Started drag: {:?}", drag);
         self.drag = Some(drag);
-        self.context.assets.sounds.click.play();
+        self.context.sfx.play(&self.context.assets.sounds.click, SfxCategory::Ui);
     }
 
     fn end_drag(&mut self) {
@@ -977,7 +2149,7 @@ impl GameState {
             DragTarget::Shop { .. } => {}
         }
 
-        self.context.assets.sounds.release.play();
+        self.context.sfx.play(&self.context.assets.sounds.release, SfxCategory::Ui);
     }
 
     fn update_drag(&mut self) {
@@ -1044,7 +2216,7 @@ impl GameState {
     }
 
     fn handle_launch_error(&mut self, error: DrillLaunchError) {
-        self.context.assets.sounds.stop.play();
+        self.context.sfx.play(&self.context.assets.sounds.stop, SfxCategory::Ui);
         match error {
             DrillLaunchError::WrongPhase => {
                 // Should be pretty obvious by itself
@@ -1078,12 +2250,131 @@ impl GameState {
             node.blink = Bounded::new_max(self.context.assets.config.error_blink_duration);
         }
     }
+
+    /// Fade the music layers in and out to match the phase and the depth,
+    /// and speed the music up while sprinting or low on fuel.
+    fn update_music(&mut self, delta_time: FloatTime) {
+        let delta_time = delta_time.as_f32();
+        let music = &self.context.music;
+        let config = &self.model.config;
+        music.play_layers(&self.context.assets.music.layers());
+
+        let drilling = matches!(self.model.phase, Phase::Drill);
+        let depth = self.model.drill.collider.position.y;
+        for (i, layer) in MusicLayer::ALL.into_iter().enumerate() {
+            let volume = if drilling {
+                config.music_volume_at(depth, layer)
+            } else if layer == MusicLayer::Calm {
+                1.0
+            } else {
+                0.0
+            };
+            music.fade_layer(i, volume);
+        }
+        music.update_layers(delta_time, config.music_fade_speed);
+
+        let intense = drilling && (self.model.drill.sprint.is_some() || self.low_fuel);
+        let target = if intense {
+            config.music_intense_speed
+        } else {
+            1.0
+        };
+        let max_change = config.music_fade_speed * delta_time;
+        self.music_speed += (target - self.music_speed).clamp(-max_change, max_change);
+        music.set_speed(self.music_speed);
+    }
+}
+
+/// Draw a mineral as its ore sprite or as a rock, dimmed by `brightness`.
+fn draw_mineral(
+    util: &UtilRender,
+    assets: &Assets,
+    model: &Model,
+    mineral: &Mineral,
+    brightness: f32,
+    framebuffer: &mut ugli::Framebuffer,
+) {
+    let sprites = &assets.sprites;
+    let palette = model.context.palette();
+    let color = match mineral.kind {
+        MineralKind::Resource(kind) => {
+            let texture = match kind {
+                ResourceKind::Coal => &sprites.coal_ore,
+                ResourceKind::Iron => &sprites.iron_ore,
+                ResourceKind::Bronze => &sprites.bronze_ore,
+                ResourceKind::Silver => &sprites.silver_ore,
+                ResourceKind::Gold => &sprites.gold_ore,
+                ResourceKind::Gem => return, // &sprites.gem,
+            };
+            // Deposits grow by area
+            let scale = (mineral.amount.max(1) as f32).sqrt();
+            if let (true, Shape::Circle { radius }) = (palette.ore_rings, mineral.collider.shape) {
+                let color = palette
+                    .resources
+                    .get(&kind)
+                    .copied()
+                    .unwrap_or(palette.default);
+                let radius = radius.as_f32();
+                model.context.geng.draw2d().circle_with_cut(
+                    framebuffer,
+                    &model.camera,
+                    mineral.collider.position.as_f32(),
+                    radius,
+                    radius + 0.08,
+                    color.map_rgb(|x| x * brightness),
+                );
+            }
+            util.draw_texture_pp(
+                texture,
+                mineral.collider.position.as_f32(),
+                vec2(0.5, 0.5),
+                mineral.collider.rotation.as_f32(),
+                scale,
+                Color::WHITE.map_rgb(|x| x * brightness),
+                &model.camera,
+                framebuffer,
+            );
+            return;
+        }
+        MineralKind::Rock => model
+            .config
+            .biome_at(mineral.collider.position.y)
+            .and_then(|biome| palette.biomes.get(&biome.kind))
+            .map_or(palette.rock, |biome| biome.rock),
+    };
+    util.draw_collider(
+        &mineral.collider,
+        color.map_rgb(|x| x * brightness),
+        &model.camera,
+        framebuffer,
+    );
+}
+
+/// Fractions of the cargo hold taken by each kind of ore, colored by the palette.
+fn cargo_segments<'a>(
+    cargo: &'a Cargo,
+    palette: &'a Palette,
+) -> impl Iterator<Item = (f32, Color)> + 'a {
+    let capacity = cargo.capacity.max(1) as f32;
+    cargo.resources.iter().map(move |(kind, &count)| {
+        let color = palette
+            .resources
+            .get(kind)
+            .copied()
+            .unwrap_or(palette.default);
+        (count as f32 / capacity, color)
+    })
 }
 
 impl geng::State for GameState {
     fn update(&mut self, delta_time: f64) {
         let delta_time = r32(delta_time as f32);
         self.real_time += delta_time;
+        self.update_music(delta_time);
+        if self.pause.is_some() {
+            return;
+        }
+
         self.turn_input = R32::ZERO;
         self.update_hover();
         self.update_drag();
@@ -1091,15 +2382,74 @@ impl geng::State for GameState {
             self.turn_input * self.model.config.drill_rotation_speed * delta_time,
         );
         self.model.update(delta_time);
+
+        // Announce the new biome
+        if self.model.biome != self.last_biome {
+            self.last_biome = self.model.biome;
+            if let (Phase::Drill, Some(biome)) = (&self.model.phase, self.model.biome) {
+                self.biome_banner = Some((biome, Bounded::new_max(r32(2.0))));
+            }
+        }
+        if let Some((_, timer)) = &mut self.biome_banner {
+            timer.change(-delta_time);
+            if timer.is_min() {
+                self.biome_banner = None;
+            }
+        }
+    }
+
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
     }
 
     fn handle_event(&mut self, event: geng::Event) {
-        let controls = &self.context.assets.controls;
+        if let Some(pause) = &mut self.pause {
+            match pause.handle_event(&event) {
+                Some(PauseAction::Resume) => self.pause = None,
+                Some(PauseAction::Quit) => {
+                    self.store_save();
+                    self.transition = Some(geng::state::Transition::Pop);
+                }
+                None => {}
+            }
+            return;
+        }
+        if let geng::Event::KeyPress {
+            key: geng::Key::Escape,
+        } = event
+        {
+            self.drag = None;
+            self.pause = Some(PauseMenu::new(
+                self.context.clone(),
+                self.cursor_screen_pos.as_f32(),
+            ));
+            return;
+        }
+
+        let controls = self.context.controls();
         if geng_utils::key::is_event_press(&event, &controls.launch) {
-            if let Err(err) = self.model.launch_drill() {
+            if let Phase::Summary { .. } = self.model.phase {
+                self.dismiss_summary();
+            } else if let Err(err) = self.model.launch_drill() {
                 self.handle_launch_error(err);
             }
         }
+        if geng_utils::key::is_event_press(&event, &controls.stats) {
+            self.show_stats = !self.show_stats;
+        }
+        if geng_utils::key::is_event_press(&event, &controls.screenshot) {
+            self.screenshot_requested = true;
+        }
+        if geng_utils::key::is_event_press(&event, &controls.recall) {
+            let winch = self
+                .model
+                .nodes
+                .find_all_nodes(|node| matches!(node.kind, NodeKind::Winch))
+                .next();
+            if let Some(winch) = winch {
+                self.model.recall_drill(winch);
+            }
+        }
 
         match event {
             geng::Event::MousePress { .. } => {
@@ -1131,7 +2481,7 @@ impl geng::State for GameState {
                 let was_hovering = self.hovering.is_some();
                 self.update_hover();
                 if !was_hovering && self.hovering.is_some() {
-                    self.context.assets.sounds.hover.play();
+                    self.context.sfx.play(&self.context.assets.sounds.hover, SfxCategory::Ui);
                 }
                 self.update_drag();
             }
@@ -1159,7 +2509,7 @@ impl geng::State for GameState {
         );
 
         let context = self.context.clone();
-        let palette = &context.assets.palette;
+        let palette = context.palette();
         let sprites = &context.assets.sprites;
         ugli::clear(framebuffer, Some(palette.background), None, None);
 
@@ -1220,18 +2570,28 @@ impl geng::State for GameState {
         }
 
         self.draw_game_ui(pixel_scale);
+        self.draw_minimap(pixel_scale);
         self.draw_shop(pixel_scale);
+        self.draw_summary(pixel_scale);
+        self.draw_stats(pixel_scale);
+        if let Some(pause) = &mut self.pause {
+            let framebuffer = &mut geng_utils::texture::attach_texture(
+                &mut self.screen_texture,
+                self.context.geng.ugli(),
+            );
+            pause.draw(&self.util, self.screen, pixel_scale, framebuffer);
+        }
 
         // Postprocessing
-        ugli::draw(
+        self.post.apply(
+            &self.screen_texture,
+            &self.context.get_options().post,
+            self.model.shake.as_f32(),
             final_framebuffer,
-            &self.context.assets.shaders.crt,
-            ugli::DrawMode::TriangleFan,
-            &self.util.unit_quad,
-            ugli::uniforms! {
-                u_texture: &self.screen_texture,
-            },
-            ugli::DrawParameters::default(),
         );
+
+        if std::mem::take(&mut self.screenshot_requested) {
+            self.save_screenshot(final_framebuffer);
+        }
     }
 }
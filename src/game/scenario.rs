@@ -0,0 +1,53 @@
+use super::*;
+
+/// A saved state to set up the game from, used to render single frames.
+#[derive(geng::asset::Load, Serialize, Deserialize, Debug, Clone)]
+#[load(serde = "ron")]
+pub struct Scenario {
+    /// Seed of the level generation.
+    pub seed: u64,
+    #[serde(default)]
+    pub money: Money,
+    /// Replaces the starting node layout.
+    #[serde(default)]
+    pub nodes: Option<Vec<Node>>,
+    /// Launch the drill and simulate it for the given number of seconds.
+    #[serde(default)]
+    pub drill_time: Option<FloatTime>,
+    #[serde(default)]
+    pub show_shop: bool,
+    #[serde(default)]
+    pub show_stats: bool,
+}
+
+impl GameState {
+    pub fn from_scenario(context: Context, scenario: Scenario) -> Result<Self> {
+        let mut state = Self::new(context);
+        state.save_progress = false;
+        state.show_shop = scenario.show_shop;
+        state.show_stats = scenario.show_stats;
+
+        let model = &mut state.model;
+        model.set_seed(scenario.seed);
+        model.money = scenario.money;
+        if let Some(nodes) = scenario.nodes {
+            model.nodes.nodes = nodes;
+        }
+        model.update(FloatTime::ZERO);
+
+        if let Some(drill_time) = scenario.drill_time {
+            model
+                .launch_drill()
+                .map_err(|err| anyhow::anyhow!("scenario failed to launch the drill: {:?}", err))?;
+            // Fixed steps, so that the same scenario always ends up in the same state
+            let delta_time = r32(1.0 / 60.0);
+            let mut time = FloatTime::ZERO;
+            while time < drill_time {
+                model.update(delta_time);
+                time += delta_time;
+            }
+        }
+
+        Ok(state)
+    }
+}
@@ -13,6 +13,8 @@ use anyhow::Result;
 use geng::prelude::*;
 
 const OPTIONS_STORAGE: &str = "options";
+const RUN_HISTORY_STORAGE: &str = "run_history";
+const SAVE_STORAGE: &str = "save";
 
 const TARGET_SCREEN_SIZE: vec2<usize> = vec2(480, 320);
 
@@ -22,6 +24,16 @@ struct Opts {
     geng: geng::CliArgs,
     #[clap(long)]
     log: Option<String>,
+    /// Render a single frame of the given scenario and exit.
+    #[clap(long)]
+    render_frame: Option<std::path::PathBuf>,
+    /// Where to save the frame rendered with `--render-frame`.
+    #[clap(long, default_value = "frame.png")]
+    out: std::path::PathBuf,
+    /// Compare the frame rendered with `--render-frame` with this image,
+    /// and fail if they differ.
+    #[clap(long)]
+    reference: Option<std::path::PathBuf>,
 }
 
 fn main() {
@@ -75,7 +87,11 @@ fn main() {
     log::info!("Please come back...");
 }
 
-async fn geng_main(geng: Geng, _opts: Opts) -> Result<()> {
+async fn geng_main(geng: Geng, opts: Opts) -> Result<()> {
+    if let Some(scenario) = &opts.render_frame {
+        return render_frame(geng, scenario, &opts.out, opts.reference.as_deref()).await;
+    }
+
     log::debug!("Initializing the loading screen...");
     let loading_assets: Rc<assets::LoadingAssets> =
         geng::asset::Load::load(geng.asset_manager(), &run_dir().join("assets"), &())
@@ -83,7 +99,8 @@ async fn geng_main(geng: Geng, _opts: Opts) -> Result<()> {
             .context("when loading assets")?;
 
     let load_everything = load_everything(geng.clone());
-    let loading_screen = menu::LoadingScreen::new(&geng, loading_assets, load_everything).run();
+    let loading_screen =
+        menu::LoadingScreen::new(&geng, loading_assets.clone(), load_everything).run();
 
     let context = loading_screen
         .await
@@ -91,12 +108,50 @@ async fn geng_main(geng: Geng, _opts: Opts) -> Result<()> {
 
     log::debug!("Loading complete!");
 
-    let state = game::GameState::new(context);
+    let state = menu::TitleScreen::new(context, loading_assets);
     geng.run_state(state).await;
 
     Ok(())
 }
 
+/// Set up the game from the scenario and draw a single frame offscreen.
+async fn render_frame(
+    geng: Geng,
+    scenario: &std::path::Path,
+    out: &std::path::Path,
+    reference: Option<&std::path::Path>,
+) -> Result<()> {
+    let scenario: game::Scenario = geng::asset::Load::load(geng.asset_manager(), scenario, &())
+        .await
+        .with_context(|| format!("when loading scenario {}", scenario.display()))?;
+    let assets = Rc::new(assets::Assets::load(geng.asset_manager()).await?);
+    // Leave out the player's options and history so the frame is the same on every machine,
+    // and the shake, which is random
+    let mut options = context::Options::default();
+    options.post.shake = 0.0;
+    let context =
+        context::Context::with_state(&geng, &assets, options, context::RunHistory::default());
+
+    let mut state = game::GameState::from_scenario(context, scenario)?;
+    let mut texture = geng_utils::texture::new_texture(geng.ugli(), TARGET_SCREEN_SIZE * 2);
+    let framebuffer = &mut geng_utils::texture::attach_texture(&mut texture, geng.ugli());
+    geng::State::draw(&mut state, framebuffer);
+    render::screenshot::save_framebuffer(framebuffer, out)?;
+
+    if let Some(reference) = reference {
+        let differ = render::screenshot::compare_framebuffer(framebuffer, reference)?;
+        if differ > 0 {
+            anyhow::bail!(
+                "{} pixels differ from {}, the new frame is saved to {}",
+                differ,
+                reference.display(),
+                out.display()
+            );
+        }
+    }
+    Ok(())
+}
+
 async fn load_everything(geng: Geng) -> Result<context::Context> {
     let manager = geng.asset_manager();
 
@@ -121,7 +121,7 @@ impl<T: 'static> geng::State for LoadingScreen<T> {
     }
 
     fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
-        let palette = &self.assets.palette;
+        let palette = self.assets.palettes.get(self.options.theme);
         ugli::clear(framebuffer, Some(palette.background), None, None);
 
         let framebuffer_size = framebuffer.size().as_f32();
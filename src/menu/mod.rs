@@ -1,3 +1,5 @@
 mod loading;
+mod pause;
+mod title;
 
-pub use self::loading::*;
+pub use self::{loading::*, pause::*, title::*};
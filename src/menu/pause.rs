@@ -0,0 +1,393 @@
+use crate::{
+    prelude::*,
+    render::{
+        post::PostEffect,
+        util::{TextRenderOptions, UtilRender},
+    },
+    ui::layout::AreaOps,
+};
+
+use geng_utils::key::EventKey;
+
+/// Options menu shown over the paused game, or as the settings on the title screen.
+pub struct PauseMenu {
+    context: Context,
+    /// Whether the menu is opened from the game, and can quit to the title.
+    in_game: bool,
+    cursor: vec2<f32>,
+    /// Widgets laid out in the last frame, for the cursor to interact with.
+    widgets: Vec<(Aabb2<f32>, Widget)>,
+    /// The slider being dragged.
+    dragging: Option<Slider>,
+    /// Index of the binding waiting for a key press.
+    rebinding: Option<usize>,
+    /// The action already using the last key pressed while rebinding.
+    rebind_conflict: Option<&'static str>,
+}
+
+/// What the game should do after the menu has handled an event.
+pub enum PauseAction {
+    Resume,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Widget {
+    Resume,
+    Slider(Slider),
+    Theme,
+    Post(PostEffect),
+    Keybind(usize),
+    ResetKeybinds,
+    Fullscreen,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slider {
+    Master,
+    Music,
+    Sfx(SfxCategory),
+}
+
+impl PauseMenu {
+    pub fn new(context: Context, cursor: vec2<f32>) -> Self {
+        Self {
+            context,
+            in_game: true,
+            cursor,
+            widgets: Vec::new(),
+            dragging: None,
+            rebinding: None,
+            rebind_conflict: None,
+        }
+    }
+
+    pub fn settings(context: Context, cursor: vec2<f32>) -> Self {
+        Self {
+            in_game: false,
+            ..Self::new(context, cursor)
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &geng::Event) -> Option<PauseAction> {
+        if let Some(index) = self.rebinding {
+            let key = match *event {
+                geng::Event::KeyPress {
+                    key: geng::Key::Escape,
+                } => {
+                    self.rebinding = None;
+                    self.rebind_conflict = None;
+                    return None;
+                }
+                geng::Event::KeyPress { key } => EventKey::Key(key),
+                // Left click is kept for the menu itself
+                geng::Event::MousePress {
+                    button: geng::MouseButton::Left,
+                } => return None,
+                geng::Event::MousePress { button } => EventKey::Mouse(button),
+                _ => return None,
+            };
+
+            // Every key triggers a single action, keep waiting for a free one
+            let controls = self.context.controls();
+            let taken = controls
+                .bindings()
+                .into_iter()
+                .enumerate()
+                .find(|(i, (_, keys))| *i != index && keys.contains(&key));
+            if let Some((_, (action, _))) = taken {
+                self.rebind_conflict = Some(action);
+                return None;
+            }
+
+            let mut options = self.context.get_options();
+            if let Some(rebind) = options.rebinds.keys_mut().into_iter().nth(index) {
+                *rebind = Some(key);
+            }
+            self.context.set_options(options);
+            self.rebinding = None;
+            self.rebind_conflict = None;
+            return None;
+        }
+
+        match *event {
+            geng::Event::KeyPress {
+                key: geng::Key::Escape,
+            } => {
+                self.stop_drag();
+                return Some(PauseAction::Resume);
+            }
+            geng::Event::CursorMove { position } => {
+                self.cursor = position.as_f32();
+                if let Some(slider) = self.dragging {
+                    self.drag_slider(slider);
+                }
+            }
+            geng::Event::MousePress {
+                button: geng::MouseButton::Left,
+            } => {
+                let widget = self
+                    .widgets
+                    .iter()
+                    .find(|(area, _)| area.contains(self.cursor))
+                    .map(|&(_, widget)| widget);
+                if let Some(widget) = widget {
+                    self.context.sfx.play(&self.context.assets.sounds.click, SfxCategory::Ui);
+                    return self.click(widget);
+                }
+            }
+            geng::Event::MouseRelease {
+                button: geng::MouseButton::Left,
+            } => self.stop_drag(),
+            _ => {}
+        }
+        None
+    }
+
+    fn click(&mut self, widget: Widget) -> Option<PauseAction> {
+        let mut options = self.context.get_options();
+        match widget {
+            Widget::Resume => return Some(PauseAction::Resume),
+            Widget::Slider(slider) => {
+                self.dragging = Some(slider);
+                self.drag_slider(slider);
+                return None;
+            }
+            Widget::Theme => {
+                let i = Theme::ALL
+                    .iter()
+                    .position(|&theme| theme == options.theme)
+                    .unwrap_or(0);
+                options.theme = Theme::ALL[(i + 1) % Theme::ALL.len()];
+            }
+            Widget::Post(effect) => {
+                let enabled = !options.post.chain.contains(&effect);
+                // Keep the passes in their canonical order
+                options.post.chain = PostEffect::ALL
+                    .into_iter()
+                    .filter(|&other| {
+                        if other == effect {
+                            enabled
+                        } else {
+                            options.post.chain.contains(&other)
+                        }
+                    })
+                    .collect();
+            }
+            Widget::Keybind(index) => {
+                self.rebinding = Some(index);
+                self.rebind_conflict = None;
+                return None;
+            }
+            Widget::ResetKeybinds => {
+                options.rebinds = default();
+            }
+            Widget::Fullscreen => {
+                let window = self.context.geng.window();
+                window.set_fullscreen(!window.is_fullscreen());
+                return None;
+            }
+            Widget::Quit => return Some(PauseAction::Quit),
+        }
+        self.context.set_options(options);
+        None
+    }
+
+    fn drag_slider(&mut self, slider: Slider) {
+        let Some(&(area, _)) = self
+            .widgets
+            .iter()
+            .find(|(_, widget)| *widget == Widget::Slider(slider))
+        else {
+            return;
+        };
+        let value = ((self.cursor.x - area.min.x) / area.width()).clamp(0.0, 1.0);
+        let mut options = self.context.get_options();
+        match slider {
+            Slider::Master => options.master_volume = value,
+            Slider::Music => options.music_volume = value,
+            Slider::Sfx(SfxCategory::Ui) => options.sfx_volumes.ui = value,
+            Slider::Sfx(SfxCategory::Drill) => options.sfx_volumes.drill = value,
+            Slider::Sfx(SfxCategory::Ambient) => options.sfx_volumes.ambient = value,
+        }
+        // Saved once the drag is over
+        self.context.preview_options(options);
+    }
+
+    fn stop_drag(&mut self) {
+        if self.dragging.take().is_some() {
+            self.context.save_options();
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        util: &UtilRender,
+        screen: Aabb2<f32>,
+        pixel_scale: f32,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let context = self.context.clone();
+        let palette = context.palette();
+        let sprites = &context.assets.sprites;
+        let fonts = &context.assets.fonts;
+        let options = context.get_options();
+        let controls = context.controls();
+        let camera = &geng::PixelPerfectCamera;
+
+        let font_size = 12.0 * pixel_scale;
+        let padding = 5.0 * pixel_scale;
+        let row_height = font_size * 1.1;
+        let label_width = font_size * 7.0;
+
+        // Dim the game underneath
+        context.geng.draw2d().quad(
+            framebuffer,
+            camera,
+            screen,
+            Color {
+                a: 0.7,
+                ..palette.background
+            },
+        );
+
+        let bindings = controls.bindings();
+        let rows = 9 + usize::from(self.in_game) + PostEffect::ALL.len() + bindings.len();
+        let panel = screen
+            .with_width(label_width * 2.5, 0.5)
+            .with_height(row_height * rows as f32 + font_size * 2.0 + padding * 2.0, 0.5);
+        util.draw_nine_slice(
+            panel,
+            palette.background,
+            &sprites.fill_thinner,
+            pixel_scale,
+            camera,
+            framebuffer,
+        );
+        util.draw_nine_slice(
+            panel,
+            palette.game_view,
+            &sprites.border_thinner,
+            pixel_scale,
+            camera,
+            framebuffer,
+        );
+        let mut panel = panel.extend_uniform(-padding);
+
+        let title_pos = panel.cut_top(font_size * 2.0);
+        util.draw_text(
+            if self.in_game { "PAUSED" } else { "SETTINGS" },
+            title_pos.center(),
+            &fonts.revolver_display,
+            TextRenderOptions::new(font_size * 1.5).color(palette.depth_text),
+            camera,
+            framebuffer,
+        );
+
+        // Label on the left and the value on the right, the whole row is clickable
+        self.widgets.clear();
+        let mut row = |label: &str, value: String, widget: Widget| {
+            let mut area = panel.cut_top(row_height);
+            self.widgets.push((area, widget));
+            let hovered = area.contains(self.cursor);
+            let label_pos = area.cut_left(label_width);
+            util.draw_text(
+                label,
+                label_pos.align_pos(vec2(0.0, 0.5)),
+                &fonts.revolver_game,
+                TextRenderOptions::new(font_size)
+                    .align(vec2(0.0, 0.5))
+                    .color(if hovered {
+                        palette.gold_text
+                    } else {
+                        palette.game_view
+                    }),
+                camera,
+                framebuffer,
+            );
+            if let Widget::Slider(slider) = widget {
+                let value = match slider {
+                    Slider::Master => options.master_volume,
+                    Slider::Music => options.music_volume,
+                    Slider::Sfx(category) => options.sfx_volumes.get(category),
+                };
+                // Only the bar part of the row is the slider
+                if let Some((slider_area, _)) = self.widgets.last_mut() {
+                    *slider_area = area;
+                }
+                let mut bar = area.with_height(4.0 * pixel_scale, 0.5);
+                util.draw_quad_outline(bar, pixel_scale, palette.fuel_back, camera, framebuffer);
+                context.geng.draw2d().quad(
+                    framebuffer,
+                    camera,
+                    bar.split_left(value),
+                    palette.fuel_front,
+                );
+            } else {
+                util.draw_text(
+                    value,
+                    area.align_pos(vec2(0.0, 0.5)),
+                    &fonts.revolver_game,
+                    TextRenderOptions::new(font_size)
+                        .align(vec2(0.0, 0.5))
+                        .color(palette.gold_text),
+                    camera,
+                    framebuffer,
+                );
+            }
+        };
+
+        let on_off = |on: bool| if on { "ON" } else { "OFF" }.to_string();
+        let resume = if self.in_game { "RESUME" } else { "BACK" };
+        row(resume, String::new(), Widget::Resume);
+        row("VOLUME", String::new(), Widget::Slider(Slider::Master));
+        row("MUSIC", String::new(), Widget::Slider(Slider::Music));
+        row("UI", String::new(), Widget::Slider(Slider::Sfx(SfxCategory::Ui)));
+        row("DRILL", String::new(), Widget::Slider(Slider::Sfx(SfxCategory::Drill)));
+        row(
+            "AMBIENT",
+            String::new(),
+            Widget::Slider(Slider::Sfx(SfxCategory::Ambient)),
+        );
+        row(
+            "PALETTE",
+            options.theme.name().to_uppercase(),
+            Widget::Theme,
+        );
+        for effect in PostEffect::ALL {
+            row(
+                effect.name(),
+                on_off(options.post.chain.contains(&effect)),
+                Widget::Post(effect),
+            );
+        }
+        for (i, (action, keys)) in bindings.into_iter().enumerate() {
+            let keys = if self.rebinding == Some(i) {
+                match self.rebind_conflict {
+                    Some(action) => format!("USED BY {}", action),
+                    None => "PRESS A KEY".to_string(),
+                }
+            } else {
+                keys.iter().map(key_name).collect::<Vec<_>>().join(", ")
+            };
+            row(action, keys, Widget::Keybind(i));
+        }
+        row("RESET KEYS", String::new(), Widget::ResetKeybinds);
+        row(
+            "FULLSCREEN",
+            on_off(context.geng.window().is_fullscreen()),
+            Widget::Fullscreen,
+        );
+        if self.in_game {
+            row("QUIT TO TITLE", String::new(), Widget::Quit);
+        }
+    }
+}
+
+fn key_name(key: &EventKey) -> String {
+    match key {
+        EventKey::Key(key) => format!("{:?}", key).to_uppercase(),
+        EventKey::Mouse(button) => format!("MOUSE {:?}", button).to_uppercase(),
+    }
+}
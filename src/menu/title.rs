@@ -0,0 +1,357 @@
+use super::{PauseAction, PauseMenu};
+
+use crate::{
+    game::GameState,
+    model::{ChallengeConfig, MusicLayer, SaveState},
+    prelude::*,
+    render::{
+        post::PostRender,
+        util::{TextRenderOptions, UtilRender},
+    },
+    ui::layout::AreaOps,
+};
+
+/// Longest seed that can be typed in.
+const MAX_SEED_DIGITS: usize = 18;
+
+pub struct TitleScreen {
+    context: Context,
+    loading_assets: Rc<LoadingAssets>,
+    util: UtilRender,
+    post: PostRender,
+    screen_texture: ugli::Texture,
+    transition: Option<geng::state::Transition>,
+
+    cursor: vec2<f32>,
+    view: TitleView,
+    /// Buttons laid out in the last frame, for the cursor to interact with.
+    buttons: Vec<(Aabb2<f32>, TitleButton)>,
+    /// Digits of the seed typed in for the new run.
+    seed_input: String,
+    save: Option<SaveState>,
+    /// Check the save again, once the game is over.
+    reload_save: bool,
+}
+
+enum TitleView {
+    Main,
+    NewRun,
+    Challenges,
+    Stats,
+    Settings(PauseMenu),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TitleButton {
+    NewRun,
+    Start,
+    Continue,
+    Challenges,
+    Challenge(usize),
+    Stats,
+    Settings,
+    Back,
+    Quit,
+}
+
+impl TitleScreen {
+    pub fn new(context: Context, loading_assets: Rc<LoadingAssets>) -> Self {
+        Self {
+            util: UtilRender::new(context.clone()),
+            post: PostRender::new(&context.geng, &context.assets),
+            screen_texture: geng_utils::texture::new_texture(context.geng.ugli(), vec2(1, 1)),
+            transition: None,
+
+            cursor: vec2::ZERO,
+            view: TitleView::Main,
+            buttons: Vec::new(),
+            seed_input: String::new(),
+            save: context.load_save(),
+            reload_save: false,
+
+            loading_assets,
+            context,
+        }
+    }
+
+    fn start(&mut self, state: GameState) {
+        self.transition = Some(geng::state::Transition::Push(Box::new(state)));
+        self.view = TitleView::Main;
+        self.reload_save = true;
+    }
+
+    fn click(&mut self, button: TitleButton) {
+        let context = self.context.clone();
+        match button {
+            TitleButton::NewRun => {
+                self.seed_input.clear();
+                self.view = TitleView::NewRun;
+            }
+            TitleButton::Start => {
+                let seed = self
+                    .seed_input
+                    .parse()
+                    .unwrap_or_else(|_| thread_rng().gen());
+                self.start(GameState::with_seed(context, seed));
+            }
+            TitleButton::Continue => {
+                if let Some(save) = self.save.clone() {
+                    self.start(GameState::from_save(context, save));
+                }
+            }
+            TitleButton::Challenges => self.view = TitleView::Challenges,
+            TitleButton::Challenge(index) => {
+                if let Some(challenge) = context.assets.config.challenges.get(index) {
+                    self.start(GameState::challenge(context.clone(), challenge));
+                }
+            }
+            TitleButton::Stats => self.view = TitleView::Stats,
+            TitleButton::Settings => {
+                self.view = TitleView::Settings(PauseMenu::settings(context, self.cursor));
+            }
+            TitleButton::Back => self.view = TitleView::Main,
+            TitleButton::Quit => self.transition = Some(geng::state::Transition::Pop),
+        }
+    }
+
+    fn type_seed(&mut self, key: geng::Key) {
+        use geng::Key;
+
+        let digit = match key {
+            Key::Digit0 | Key::Numpad0 => '0',
+            Key::Digit1 | Key::Numpad1 => '1',
+            Key::Digit2 | Key::Numpad2 => '2',
+            Key::Digit3 | Key::Numpad3 => '3',
+            Key::Digit4 | Key::Numpad4 => '4',
+            Key::Digit5 | Key::Numpad5 => '5',
+            Key::Digit6 | Key::Numpad6 => '6',
+            Key::Digit7 | Key::Numpad7 => '7',
+            Key::Digit8 | Key::Numpad8 => '8',
+            Key::Digit9 | Key::Numpad9 => '9',
+            Key::Backspace => {
+                self.seed_input.pop();
+                return;
+            }
+            Key::Enter => {
+                self.click(TitleButton::Start);
+                return;
+            }
+            _ => return,
+        };
+        if self.seed_input.len() < MAX_SEED_DIGITS {
+            self.seed_input.push(digit);
+        }
+    }
+
+    fn draw_menu(&mut self, pixel_scale: f32) {
+        let context = self.context.clone();
+        let framebuffer = &mut geng_utils::texture::attach_texture(
+            &mut self.screen_texture,
+            context.geng.ugli(),
+        );
+        let palette = context.palette();
+        let fonts = &context.assets.fonts;
+        let camera = &geng::PixelPerfectCamera;
+        let screen = Aabb2::ZERO.extend_positive(framebuffer.size().as_f32());
+
+        // Logo
+        let mut area = screen;
+        let logo_area = area.split_top(0.4);
+        let logo = geng_utils::pixel::pixel_perfect_aabb(
+            logo_area.center(),
+            vec2(0.5, 0.5),
+            self.loading_assets.title.size() * (pixel_scale.max(1.0) as usize),
+            camera,
+            screen.size(),
+        );
+        context.geng.draw2d().textured_quad(
+            framebuffer,
+            camera,
+            logo,
+            &self.loading_assets.title,
+            Color::WHITE,
+        );
+
+        let font_size = 16.0 * pixel_scale;
+        let row_height = font_size * 1.4;
+        let mut rows = area.with_width(font_size * 16.0, 0.5);
+
+        // Rows of text, the ones with a button are clickable
+        let mut lines: Vec<(String, Option<TitleButton>)> = Vec::new();
+        match &self.view {
+            TitleView::Main => {
+                lines.push(("NEW RUN".into(), Some(TitleButton::NewRun)));
+                if self.save.is_some() {
+                    lines.push(("CONTINUE".into(), Some(TitleButton::Continue)));
+                }
+                lines.push(("CHALLENGES".into(), Some(TitleButton::Challenges)));
+                lines.push(("STATS".into(), Some(TitleButton::Stats)));
+                lines.push(("SETTINGS".into(), Some(TitleButton::Settings)));
+                lines.push(("QUIT".into(), Some(TitleButton::Quit)));
+            }
+            TitleView::NewRun => {
+                let seed = if self.seed_input.is_empty() {
+                    "RANDOM".to_string()
+                } else {
+                    self.seed_input.clone()
+                };
+                lines.push((format!("SEED: {}", seed), None));
+                lines.push(("TYPE DIGITS TO SET THE SEED".into(), None));
+                lines.push(("START".into(), Some(TitleButton::Start)));
+                lines.push(("BACK".into(), Some(TitleButton::Back)));
+            }
+            TitleView::Challenges => {
+                for (i, challenge) in context.assets.config.challenges.iter().enumerate() {
+                    lines.push((challenge_label(challenge), Some(TitleButton::Challenge(i))));
+                }
+                lines.push(("BACK".into(), Some(TitleButton::Back)));
+            }
+            TitleView::Stats => {
+                let history = context.history();
+                lines.push((format!("RUNS: {}", history.total_runs()), None));
+                if let Some(depth) = history.best_depth() {
+                    lines.push((
+                        format!("BEST DEPTH: {}", depth.as_f32().ceil() as i64),
+                        None,
+                    ));
+                }
+                if let Some(haul) = history.best_haul() {
+                    lines.push((format!("BEST HAUL: {}", haul), None));
+                }
+                lines.push(("BACK".into(), Some(TitleButton::Back)));
+            }
+            TitleView::Settings(_) => {}
+        }
+
+        self.buttons.clear();
+        for (line, button) in lines {
+            let row = rows.cut_top(row_height);
+            let color = match button {
+                None => palette.game_view,
+                Some(_) if row.contains(self.cursor) => palette.gold_text,
+                Some(_) => palette.depth_text,
+            };
+            self.util.draw_text(
+                line,
+                row.center(),
+                &fonts.revolver_game,
+                TextRenderOptions::new(font_size).color(color),
+                camera,
+                framebuffer,
+            );
+            if let Some(button) = button {
+                self.buttons.push((row, button));
+            }
+        }
+    }
+}
+
+fn challenge_label(challenge: &ChallengeConfig) -> String {
+    if challenge.money > 0 {
+        format!("{} ({} GOLD)", challenge.name, challenge.money)
+    } else {
+        challenge.name.clone()
+    }
+}
+
+impl geng::State for TitleScreen {
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+
+    fn update(&mut self, delta_time: f64) {
+        if std::mem::take(&mut self.reload_save) {
+            self.save = self.context.load_save();
+        }
+
+        // The same layers as in the game, so it picks up the calm one without a restart
+        let music = &self.context.music;
+        music.play_layers(&self.context.assets.music.layers());
+        for (i, layer) in MusicLayer::ALL.into_iter().enumerate() {
+            music.fade_layer(i, if layer == MusicLayer::Calm { 1.0 } else { 0.0 });
+        }
+        music.set_speed(1.0);
+        music.update_layers(delta_time as f32, self.context.assets.config.music_fade_speed);
+    }
+
+    fn handle_event(&mut self, event: geng::Event) {
+        if let TitleView::Settings(settings) = &mut self.view {
+            if let Some(PauseAction::Resume) = settings.handle_event(&event) {
+                self.view = TitleView::Main;
+            }
+            return;
+        }
+
+        match event {
+            geng::Event::CursorMove { position } => {
+                self.cursor = position.as_f32();
+            }
+            geng::Event::MousePress {
+                button: geng::MouseButton::Left,
+            } => {
+                let button = self
+                    .buttons
+                    .iter()
+                    .find(|(area, _)| area.contains(self.cursor))
+                    .map(|&(_, button)| button);
+                if let Some(button) = button {
+                    self.context.sfx.play(&self.context.assets.sounds.click, SfxCategory::Ui);
+                    self.click(button);
+                }
+            }
+            geng::Event::KeyPress {
+                key: geng::Key::Escape,
+            } => {
+                self.view = TitleView::Main;
+            }
+            geng::Event::KeyPress { key } => {
+                if let TitleView::NewRun = self.view {
+                    self.type_seed(key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, final_framebuffer: &mut ugli::Framebuffer) {
+        let pixel_scale = final_framebuffer.size().as_f32() / crate::TARGET_SCREEN_SIZE.as_f32();
+        let pixel_scale = pixel_scale.x.min(pixel_scale.y).floor().max(0.25);
+
+        geng_utils::texture::update_texture_size(
+            &mut self.screen_texture,
+            final_framebuffer.size(),
+            self.context.geng.ugli(),
+        );
+        let screen = Aabb2::ZERO.extend_positive(final_framebuffer.size().as_f32());
+
+        // Background
+        {
+            let framebuffer = &mut geng_utils::texture::attach_texture(
+                &mut self.screen_texture,
+                self.context.geng.ugli(),
+            );
+            ugli::clear(framebuffer, Some(self.context.palette().background), None, None);
+            let texture = &self.loading_assets.background;
+            self.context.geng.draw2d().textured_quad(
+                framebuffer,
+                &geng::PixelPerfectCamera,
+                screen,
+                texture,
+                Color::WHITE,
+            );
+        }
+
+        self.draw_menu(pixel_scale);
+        if let TitleView::Settings(settings) = &mut self.view {
+            let framebuffer = &mut geng_utils::texture::attach_texture(
+                &mut self.screen_texture,
+                self.context.geng.ugli(),
+            );
+            settings.draw(&self.util, screen, pixel_scale, framebuffer);
+        }
+
+        // Same look as the game
+        let options = self.context.get_options();
+        self.post.apply(&self.screen_texture, &options.post, 0.0, final_framebuffer);
+    }
+}
@@ -12,6 +12,14 @@ pub struct Collision {
     pub penetration: Coord,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct Cast {
+    /// Fraction of the movement at which the contact happens.
+    pub time: Coord,
+    /// Normal vector pointing away from the moving body.
+    pub normal: vec2<Coord>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default)]
 pub struct Transform {
@@ -157,4 +165,47 @@ impl Collider {
             }
         })
     }
+
+    /// Sweep the collider by `delta` and return the first contact with the other collider.
+    pub fn cast(&self, delta: vec2<Coord>, other: &Self) -> Option<Cast> {
+        let offset = (other.position - self.position).as_f32();
+        let delta = delta.as_f32();
+
+        let self_angle = self.rotation.as_radians().as_f32();
+        let self_iso = parry2d::math::Isometry::rotation(self_angle);
+        let self_vel = parry2d::na::Vector2::new(delta.x, delta.y);
+        let self_shape = self.shape.to_parry();
+
+        let other_angle = other.rotation.as_radians().as_f32();
+        let other_iso = parry2d::math::Isometry::new(
+            parry2d::na::Vector2::new(offset.x, offset.y),
+            other_angle,
+        );
+        let other_vel = parry2d::na::Vector2::zeros();
+        let other_shape = other.shape.to_parry();
+
+        let max_toi = 1.0;
+        let stop_at_penetration = true;
+        parry2d::query::time_of_impact(
+            &self_iso,
+            &self_vel,
+            &*self_shape,
+            &other_iso,
+            &other_vel,
+            &*other_shape,
+            max_toi,
+            stop_at_penetration,
+        )
+        .unwrap()
+        .map(|toi| {
+            // The normal is in the local space of the moving body
+            let normal = toi.normal1.into_inner();
+            Cast {
+                time: Coord::new(toi.toi),
+                normal: vec2(normal.x, normal.y)
+                    .map(Coord::new)
+                    .rotate(self.rotation),
+            }
+        })
+    }
 }
@@ -0,0 +1,49 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CreatureKind {
+    /// Wanders through the strata eating the ore it comes across.
+    Worm,
+    /// Latches onto the drill and drains fuel until shaken off by sprinting.
+    Pest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreatureConfig {
+    pub size: Coord,
+    pub speed: Coord,
+    pub health: Coord,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreatureGeneration {
+    pub kind: CreatureKind,
+    pub density: R32,
+}
+
+#[derive(SplitFields, Debug, Clone)]
+pub struct Creature {
+    pub kind: CreatureKind,
+    pub position: vec2<Coord>,
+    pub velocity: vec2<Coord>,
+    pub radius: Coord,
+    pub health: Bounded<Coord>,
+    /// Time until the creature picks a new direction.
+    pub wander: FloatTime,
+    /// Offset from the drill, if latched onto it.
+    pub latched: Option<vec2<Coord>>,
+}
+
+impl Creature {
+    pub fn new(kind: CreatureKind, position: vec2<Coord>, config: &CreatureConfig) -> Self {
+        Self {
+            kind,
+            position,
+            velocity: vec2::ZERO,
+            radius: config.size,
+            health: Bounded::new_max(config.health),
+            wander: FloatTime::ZERO,
+            latched: None,
+        }
+    }
+}
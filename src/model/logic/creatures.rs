@@ -0,0 +1,215 @@
+use super::*;
+
+impl Model {
+    pub(super) fn update_creatures(&mut self, delta_time: FloatTime) {
+        let Phase::Drill = self.phase else { return };
+
+        let rng = &mut self.rng;
+        let drill = self.drill.collider.position;
+        let sprinting = self.drill.sprint.is_some();
+
+        // Movement
+        let mut latched_pests = 0;
+        let mut shaken_off = false;
+        for (kind, position, velocity, radius, health, wander, latched) in query!(
+            self.creatures,
+            (
+                &kind,
+                &mut position,
+                &mut velocity,
+                &radius,
+                &mut health,
+                &mut wander,
+                &mut latched
+            )
+        ) {
+            let Some(config) = self.config.creatures.get(kind) else {
+                continue;
+            };
+
+            if let Some(offset) = *latched {
+                if !sprinting {
+                    // Ride along with the drill
+                    *position = drill + offset;
+                    latched_pests += 1;
+                    continue;
+                }
+                // Thrown off by the sprint
+                *latched = None;
+                *velocity = offset.normalize_or_zero() * config.speed;
+                *wander = r32(1.0);
+                shaken_off = true;
+            }
+
+            *wander -= delta_time;
+            let distance = (drill - *position).len();
+            match kind {
+                CreatureKind::Pest if !sprinting && distance < self.config.pest_sense_range => {
+                    // Chase the drill
+                    *velocity = (drill - *position).normalize_or_zero() * config.speed;
+                }
+                _ if *wander <= FloatTime::ZERO => {
+                    // Pick a new direction, worms mostly follow the strata
+                    let angle = match kind {
+                        CreatureKind::Worm => {
+                            let angle = rng.gen_range(-30.0..=30.0);
+                            if rng.gen_bool(0.5) {
+                                angle
+                            } else {
+                                180.0 - angle
+                            }
+                        }
+                        CreatureKind::Pest => rng.gen_range(0.0..360.0),
+                    };
+                    *velocity = Angle::from_degrees(r32(angle)).unit_vec() * config.speed;
+                    *wander = r32(rng.gen_range(1.0..=3.0));
+                }
+                _ => {}
+            }
+
+            *position += *velocity * delta_time;
+            let min_x = self.bounds.min.x + *radius;
+            let max_x = self.bounds.max.x - *radius;
+            if position.x < min_x || position.x > max_x {
+                position.x = position.x.clamp(min_x, max_x);
+                velocity.x = -velocity.x;
+            }
+
+            if distance < self.config.drill_size + *radius {
+                match kind {
+                    CreatureKind::Worm => {
+                        // Ground up by the drill
+                        health.set_ratio(R32::ZERO);
+                    }
+                    CreatureKind::Pest => {
+                        if !sprinting {
+                            *latched = Some(*position - drill);
+                            latched_pests += 1;
+                        }
+                    }
+                }
+            }
+        }
+        self.drill.latched_pests = latched_pests;
+        if shaken_off {
+            self.context.sfx.play(&self.context.assets.sounds.bounce, SfxCategory::Ambient);
+        }
+
+        self.feed_worms();
+        self.zap_creatures();
+
+        // Remove the dead
+        let mut dead = Vec::new();
+        for (id, kind, position, health) in
+            query!(self.creatures, (id, &kind, &position, &health))
+        {
+            if health.is_min() {
+                dead.push((id, *kind, *position));
+            }
+        }
+        for (id, kind, position) in dead {
+            self.creatures.remove(id);
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::Creature(kind),
+                density: r32(15.0),
+                distribution: ParticleDistribution::Circle {
+                    center: position,
+                    radius: r32(0.3),
+                },
+                size: r32(0.05)..=r32(0.15),
+                ..default()
+            });
+        }
+    }
+
+    /// Worms eat the ore they come across.
+    fn feed_worms(&mut self) {
+        let mut worms = Vec::new();
+        for (kind, position, radius) in query!(self.creatures, (&kind, &position, &radius)) {
+            if let CreatureKind::Worm = kind {
+                worms.push(Collider::circle(*position, *radius));
+            }
+        }
+        if worms.is_empty() {
+            return;
+        }
+
+        let mut eaten = Vec::new();
+        self.minerals.retain(|mineral| {
+            let MineralKind::Resource(kind) = mineral.kind else {
+                return true;
+            };
+            if !worms.iter().any(|worm| worm.check(&mineral.collider)) {
+                return true;
+            }
+            eaten.push((kind, mineral.collider.position));
+            false
+        });
+        for (kind, position) in eaten {
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::Collect(kind),
+                distribution: ParticleDistribution::Circle {
+                    center: position,
+                    radius: self.config.mineral_size,
+                },
+                ..default()
+            });
+        }
+    }
+
+    /// Powered zappers hit the closest creature in range of the drill.
+    fn zap_creatures(&mut self) {
+        let drill = self.drill.collider.position;
+        for node_i in 0..self.nodes.nodes.len() {
+            let node = &self.nodes.nodes[node_i];
+            let NodeKind::Zapper { cooldown } = &node.kind else {
+                continue;
+            };
+            if !node.is_powered || cooldown.is_above_min() {
+                continue;
+            }
+
+            let mut target = None;
+            for (id, position) in query!(self.creatures, (id, &position)) {
+                let distance = (*position - drill).len();
+                if distance > self.config.zapper_range {
+                    continue;
+                }
+                if target.map_or(true, |(_, _, closest)| distance < closest) {
+                    target = Some((id, *position, distance));
+                }
+            }
+            let Some((target, position, _)) = target else {
+                continue;
+            };
+
+            if let NodeKind::Zapper { cooldown } = &mut self.nodes.nodes[node_i].kind {
+                cooldown.set_ratio(r32(1.0));
+            }
+
+            for (id, health) in query!(self.creatures, (id, &mut health)) {
+                if id == target {
+                    health.change(-self.config.zapper_damage);
+                }
+            }
+
+            // Sparks along the bolt
+            let steps = 6;
+            for i in 0..=steps {
+                let t = r32(i as f32 / steps as f32);
+                self.particles_queue.push(SpawnParticles {
+                    kind: ParticleKind::Zap,
+                    density: r32(20.0),
+                    distribution: ParticleDistribution::Circle {
+                        center: drill + (position - drill) * t,
+                        radius: r32(0.1),
+                    },
+                    size: r32(0.03)..=r32(0.08),
+                    lifetime: r32(0.2)..=r32(0.4),
+                    ..default()
+                });
+            }
+            self.context.sfx.play(&self.context.assets.sounds.collide, SfxCategory::Ambient);
+        }
+    }
+}
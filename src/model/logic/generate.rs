@@ -1,12 +1,24 @@
 use super::*;
 
 impl Model {
+    /// Restart the level generation from the given seed.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.level_rng = StdRng::seed_from_u64(seed);
+        self.rng = StdRng::seed_from_u64(seed);
+        self.generate_level();
+    }
+
     pub fn generate_level(&mut self) {
         log::debug!("Generating next level..");
+        self.level_seed = self.level_rng.gen();
 
         // Reset drill
         self.drill.collider.position = vec2(Coord::ZERO, self.ground_level);
         self.drill.collider.rotation = Angle::from_degrees(r32(-90.0));
+        self.drill.hull.set_ratio(r32(1.0));
+        self.drill.vision_boost = None;
+        self.drill.latched_pests = 0;
 
         // Reset nodes
         for node in &mut self.nodes.nodes {
@@ -28,10 +40,23 @@ impl Model {
                 NodeKind::Battery => {}
                 NodeKind::Vision { .. } => {}
                 NodeKind::Speed { .. } => {}
+                NodeKind::Armor { .. } => {}
+                NodeKind::Repair(bounded) => {
+                    bounded.set_ratio(r32(1.0));
+                }
+                NodeKind::Cargo | NodeKind::CargoExpansion => {}
+                NodeKind::Winch => {}
+                NodeKind::Elevator { .. } => {}
+                NodeKind::Filter | NodeKind::Cooling | NodeKind::Pump => {}
+                NodeKind::Tuner { .. } => {}
+                NodeKind::Zapper { cooldown } | NodeKind::Scanner { cooldown, .. } => {
+                    cooldown.set_ratio(r32(0.0));
+                }
             }
         }
 
         self.camera.center = self.drill.collider.position.as_f32();
+        self.shake = R32::ZERO;
         self.bounds = Aabb2::from_corners(
             vec2(-self.config.map_width / r32(2.0), r32(-10000.0)),
             vec2(self.config.map_width / r32(2.0), r32(10000.0)),
@@ -39,6 +64,14 @@ impl Model {
 
         // Spawn minerals
         self.minerals.clear();
+        self.caves.clear();
+        self.tunnels.clear();
+        self.explored.clear();
+        self.scanner_ping = None;
+        self.hazards.clear();
+        self.pickups.clear();
+        self.sell_points.clear();
+        self.creatures = default();
         self.depth_generated = self.ground_level;
         self.spawn_depths();
 
@@ -56,8 +89,39 @@ impl Model {
     }
 
     fn generate_strip(&mut self, y_max: Coord, y_min: Coord) {
-        let mut rng = thread_rng();
+        // Every strip gets its own generator, so the level does not depend
+        // on how far ahead it has been generated
+        let strip_seed = u64::from(y_max.as_f32().to_bits());
+        let mut rng = StdRng::seed_from_u64(self.level_seed ^ strip_seed);
 
+        // Sell point every so often
+        let interval = self.config.sell_point_interval;
+        let sell_index = |y: Coord| (-y / interval).floor();
+        if sell_index(y_min) > sell_index(y_max) {
+            let size = r32(0.5);
+            let position = vec2(
+                rng.gen_range(self.bounds.min.x + size..=self.bounds.max.x - size),
+                -sell_index(y_min) * interval,
+            );
+            self.sell_points.push(SellPoint {
+                collider: Collider::circle(position, size),
+                used: false,
+            });
+        }
+
+        // Caves, the minerals of this strip below skip them.
+        // The minerals generated before are left alone, they might be in view already
+        let cave_density = self
+            .config
+            .biome_at(y_max)
+            .map_or(R32::ZERO, |biome| biome.cave_density);
+        let mut new_caves = Vec::new();
+        for _ in 0..self.roll_spawns(&mut rng, y_max - y_min, cave_density) {
+            new_caves.push(self.generate_cave(&mut rng, y_max, y_min));
+        }
+
+        // Global minerals by depth range
+        let mut spawns = Vec::new();
         for (&mineral_kind, config) in &self.config.minerals {
             for config in &config.generation {
                 let [mut mineral_min, mut mineral_max] = config.range;
@@ -69,23 +133,196 @@ impl Model {
                     continue;
                 }
 
-                let density = config.density;
-                let n_spawns = (y_max - y_min) * self.config.map_width * density;
-                let n_spawns = n_spawns.floor().as_f32() as usize
-                    + rng.gen_bool(n_spawns.fract().as_f32() as f64) as usize;
-                for _ in 0..n_spawns {
-                    // Spawn a mineral
-                    let position = vec2(
-                        rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
-                        rng.gen_range(y_min..=y_max),
-                    );
-                    self.minerals.push(Mineral {
-                        collider: Collider::circle(position, r32(0.15)),
-                        kind: mineral_kind,
-                        amount: 1,
-                    });
+                spawns.push((mineral_kind, config.density, config.amount));
+            }
+        }
+
+        // Biome specific minerals, rocks, hazards and creatures
+        let mut rocks = None;
+        let mut hazards = Vec::new();
+        let mut creatures = Vec::new();
+        if let Some(biome) = self.config.biome_at(y_max) {
+            hazards.clone_from(&biome.hazards);
+            creatures.clone_from(&biome.creatures);
+            spawns.extend(
+                biome
+                    .minerals
+                    .iter()
+                    .map(|mineral| (mineral.kind, mineral.density, mineral.amount)),
+            );
+            rocks = Some((biome.rock_density, biome.rock_size));
+        }
+
+        // Loose ore on the cave floors
+        let ores: Vec<MineralKind> = spawns
+            .iter()
+            .map(|&(kind, ..)| kind)
+            .filter(|kind| matches!(kind, MineralKind::Resource(_)))
+            .collect();
+        for cave in &new_caves {
+            if ores.is_empty() {
+                break;
+            }
+            let [ore_min, ore_max] = self.config.cave_ore;
+            for _ in 0..rng.gen_range(ore_min..=ore_max.max(ore_min)) {
+                let Some(part) = cave.parts.choose(&mut rng) else {
+                    continue;
+                };
+                let Shape::Circle { radius } = part.shape else {
+                    continue;
+                };
+                let radius = radius - self.config.mineral_size;
+                let dx = rng.gen_range(-radius / r32(2.0)..=radius / r32(2.0));
+                let dy = -(radius * radius - dx * dx).sqrt();
+                let id = self.new_mineral_id();
+                self.minerals.push(Mineral {
+                    id,
+                    collider: Collider::circle(
+                        part.position + vec2(dx, dy),
+                        self.config.mineral_size,
+                    ),
+                    kind: *ores.choose(&mut rng).unwrap(),
+                    amount: 1,
+                    progress: R32::ZERO,
+                });
+            }
+        }
+        self.caves.extend(new_caves);
+
+        for (mineral_kind, density, [amount_min, amount_max]) in spawns {
+            for _ in 0..self.roll_spawns(&mut rng, y_max - y_min, density) {
+                // Spawn a mineral
+                let position = vec2(
+                    rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
+                    rng.gen_range(y_min..=y_max),
+                );
+                if self.in_cave(position) {
+                    continue;
                 }
+                let amount = rng
+                    .gen_range(amount_min..=amount_max.max(amount_min))
+                    .max(1);
+                let id = self.new_mineral_id();
+                self.minerals.push(Mineral {
+                    id,
+                    collider: Collider::circle(
+                        position,
+                        self.config.mineral_size * r32(amount as f32).sqrt(),
+                    ),
+                    kind: mineral_kind,
+                    amount,
+                    progress: R32::ZERO,
+                });
             }
         }
+
+        if let Some((density, [size_min, size_max])) = rocks {
+            for _ in 0..self.roll_spawns(&mut rng, y_max - y_min, density) {
+                let position = vec2(
+                    rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
+                    rng.gen_range(y_min..=y_max),
+                );
+                if self.in_cave(position) {
+                    continue;
+                }
+                let size = rng.gen_range(size_min..=size_max.max(size_min));
+                let id = self.new_mineral_id();
+                self.minerals.push(Mineral {
+                    id,
+                    collider: Collider::circle(position, size),
+                    kind: MineralKind::Rock,
+                    amount: 1,
+                    progress: R32::ZERO,
+                });
+            }
+        }
+
+        for config in &self.config.pickups {
+            let [a, b] = config.range;
+            if !(a.min(b)..=a.max(b)).contains(&y_max) {
+                continue;
+            }
+            for _ in 0..self.roll_spawns(&mut rng, y_max - y_min, config.density) {
+                let position = vec2(
+                    rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
+                    rng.gen_range(y_min..=y_max),
+                );
+                self.pickups.push(Pickup {
+                    collider: Collider::circle(position, r32(0.4)),
+                    kind: config.kind,
+                });
+            }
+        }
+
+        for config in hazards {
+            let [size_min, size_max] = config.size;
+            for _ in 0..self.roll_spawns(&mut rng, y_max - y_min, config.density) {
+                let position = vec2(
+                    rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
+                    rng.gen_range(y_min..=y_max),
+                );
+                let size = rng.gen_range(size_min..=size_max.max(size_min));
+                self.hazards.push(Hazard {
+                    collider: Collider::circle(position, size),
+                    kind: config.kind,
+                });
+            }
+        }
+
+        for generation in creatures {
+            let Some(config) = self.config.creatures.get(&generation.kind) else {
+                continue;
+            };
+            for _ in 0..self.roll_spawns(&mut rng, y_max - y_min, generation.density) {
+                let position = vec2(
+                    rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
+                    rng.gen_range(y_min..=y_max),
+                );
+                self.creatures.insert(Creature::new(generation.kind, position, config));
+            }
+        }
+    }
+
+    /// Generate a cave as a horizontal chain of overlapping circles.
+    fn generate_cave(&self, rng: &mut impl Rng, y_max: Coord, y_min: Coord) -> Cave {
+        let [size_min, size_max] = self.config.cave_size;
+        let [parts_min, parts_max] = self.config.cave_parts;
+
+        let mut position = vec2(
+            rng.gen_range(self.bounds.min.x..=self.bounds.max.x),
+            rng.gen_range(y_min..=y_max),
+        );
+        let direction = if rng.gen_bool(0.5) { r32(1.0) } else { r32(-1.0) };
+        let parts = (0..rng.gen_range(parts_min..=parts_max.max(parts_min)))
+            .map(|_| {
+                let radius = rng.gen_range(size_min..=size_max.max(size_min));
+                let part = Collider::circle(position, radius);
+                position += vec2(
+                    direction * radius * r32(rng.gen_range(0.6..=1.0)),
+                    radius * r32(rng.gen_range(-0.3..=0.3)),
+                );
+                position.x = position.x.clamp(self.bounds.min.x, self.bounds.max.x);
+                position.y = position.y.clamp(y_min, y_max);
+                part
+            })
+            .collect();
+        Cave { parts }
+    }
+
+    fn new_mineral_id(&mut self) -> MineralId {
+        let id = self.next_mineral_id;
+        self.next_mineral_id += 1;
+        id
+    }
+
+    fn in_cave(&self, position: vec2<Coord>) -> bool {
+        self.caves.iter().any(|cave| cave.contains(position))
+    }
+
+    /// Number of spawns in a strip of the given height, rounding randomly.
+    fn roll_spawns(&self, rng: &mut impl Rng, height: Coord, density: R32) -> usize {
+        let n_spawns = height * self.config.map_width * density;
+        n_spawns.floor().as_f32() as usize
+            + rng.gen_bool(n_spawns.fract().as_f32() as f64) as usize
     }
 }
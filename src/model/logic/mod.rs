@@ -1,8 +1,99 @@
+mod creatures;
 mod generate;
 
 use super::*;
 
 impl Nodes {
+    /// Hash of the node kinds and the connections between them,
+    /// ignoring positions and the state of the nodes.
+    ///
+    /// The hash is stored in the run history, so it uses FNV-1a over fixed tags
+    /// to stay the same across builds.
+    pub fn layout_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET;
+        let mut write = |value: u64| {
+            for byte in value.to_le_bytes() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        for node in &self.nodes {
+            write(node.kind.layout_tag());
+            for conn in &node.connections {
+                write(match conn.kind {
+                    ConnectionKind::Normal => 0,
+                    ConnectionKind::Fuel => 1,
+                    ConnectionKind::Upgrade => 2,
+                    ConnectionKind::Drill => 3,
+                });
+                match conn.connected_to {
+                    None => write(0),
+                    Some(to) => {
+                        write(1);
+                        write(to.node as u64);
+                        write(to.connection as u64);
+                    }
+                }
+            }
+        }
+        hash
+    }
+
+    /// Indices of the nodes connected to the given one, directly or through other nodes,
+    /// in breadth-first order starting with the node itself.
+    pub fn connected_nodes(&self, start: usize) -> Vec<usize> {
+        let mut connected = Vec::new();
+        let mut checked = HashSet::new();
+        let mut to_check = VecDeque::new();
+        to_check.push_back(start);
+        while let Some(i) = to_check.pop_front() {
+            if !checked.insert(i) {
+                continue;
+            }
+            let Some(node) = self.nodes.get(i) else {
+                continue;
+            };
+            connected.push(i);
+            for conn in &node.connections {
+                if let Some(to) = conn.connected_to {
+                    to_check.push_back(to.node);
+                }
+            }
+        }
+        connected
+    }
+
+    /// Indices of the upgrade and tuner nodes chained to the given one.
+    pub fn upgrade_chain(&self, start: usize) -> Vec<usize> {
+        let mut chain = Vec::new();
+        let mut checked = HashSet::new();
+        let mut to_check = VecDeque::new();
+        to_check.push_back(start);
+        while let Some(i) = to_check.pop_front() {
+            if !checked.insert(i) {
+                continue;
+            }
+            let Some(node) = self.nodes.get(i) else {
+                continue;
+            };
+            if i != start {
+                if !matches!(node.kind, NodeKind::Upgrade | NodeKind::Tuner { .. }) {
+                    continue;
+                }
+                chain.push(i);
+            }
+            for conn in &node.connections {
+                if let Some(to) = conn.connected_to {
+                    to_check.push_back(to.node);
+                }
+            }
+        }
+        chain
+    }
+
     pub fn find_all_nodes<'a>(
         &'a self,
         mut predicate: impl FnMut(&Node) -> bool + 'a,
@@ -15,6 +106,39 @@ impl Nodes {
     }
 }
 
+impl NodeKind {
+    /// Fixed number for each kind of node, used in [`Nodes::layout_hash`].
+    /// Never change the existing numbers.
+    fn layout_tag(&self) -> u64 {
+        match self {
+            NodeKind::Power => 0,
+            NodeKind::Fuel(_) => 1,
+            NodeKind::Shop { .. } => 2,
+            NodeKind::Drill { .. } => 3,
+            NodeKind::TurnLeft => 4,
+            NodeKind::TurnRight => 5,
+            NodeKind::Sprint { .. } => 6,
+            NodeKind::Upgrade => 7,
+            NodeKind::Battery => 8,
+            NodeKind::Vision { .. } => 9,
+            NodeKind::Speed { .. } => 10,
+            NodeKind::CoalFuel(_) => 11,
+            NodeKind::Armor { .. } => 12,
+            NodeKind::Repair(_) => 13,
+            NodeKind::Cargo => 14,
+            NodeKind::CargoExpansion => 15,
+            NodeKind::Winch => 16,
+            NodeKind::Elevator { .. } => 17,
+            NodeKind::Filter => 18,
+            NodeKind::Cooling => 19,
+            NodeKind::Pump => 20,
+            NodeKind::Zapper { .. } => 21,
+            NodeKind::Scanner { .. } => 22,
+            NodeKind::Tuner { .. } => 23,
+        }
+    }
+}
+
 impl Model {
     pub fn update(&mut self, delta_time: FloatTime) {
         self.simulation_time += delta_time;
@@ -22,19 +146,42 @@ impl Model {
         self.update_shop();
 
         match self.phase {
-            Phase::Setup => {}
-            Phase::Drill => {
-                self.move_drill(delta_time);
-                self.collide_drill(delta_time);
-                self.use_fuel(delta_time);
-                self.spawn_depths();
-            }
+            Phase::Setup | Phase::Summary { .. } => {}
+            Phase::Drill => self.update_drill(delta_time),
         }
 
+        self.biome = self
+            .config
+            .biome_at(self.drill.collider.position.y)
+            .map(|biome| biome.kind);
+
         self.update_camera(delta_time);
         self.process_particles(delta_time);
     }
 
+    fn update_drill(&mut self, delta_time: FloatTime) {
+        let steps: [fn(&mut Self, FloatTime); 11] = [
+            Self::move_drill,
+            Self::collide_drill,
+            Self::update_vision_boost,
+            Self::apply_hazards,
+            Self::update_creatures,
+            |model, _| model.explore(),
+            Self::update_scanner,
+            Self::repair_hull,
+            Self::use_fuel,
+            Self::update_run_time,
+            |model, _| model.spawn_depths(),
+        ];
+        for step in steps {
+            // Any step can end the run, the rest only make sense while drilling
+            if !matches!(self.phase, Phase::Drill) {
+                break;
+            }
+            step(self, delta_time);
+        }
+    }
+
     pub fn launch_drill(&mut self) -> Result<(), DrillLaunchError> {
         let Phase::Setup = self.phase else {
             return Err(DrillLaunchError::WrongPhase);
@@ -44,19 +191,10 @@ impl Model {
 
         // 1. Fuel and drill are connected
         {
-            let mut to_check = VecDeque::new();
-            to_check.push_front(0);
-            let mut checked = HashSet::new();
             let mut has_fuel = false;
             let mut has_drill = false;
-            while let Some(i) = to_check.pop_front() {
-                if !checked.insert(i) {
-                    continue;
-                }
-                let Some(node) = self.nodes.nodes.get(i) else {
-                    continue;
-                };
-                match node.kind {
+            for i in self.nodes.connected_nodes(0) {
+                match self.nodes.nodes[i].kind {
                     NodeKind::Fuel(..) => {
                         has_fuel = true;
                     }
@@ -65,11 +203,6 @@ impl Model {
                     }
                     _ => (),
                 }
-                for conn in &node.connections {
-                    if let Some(to) = conn.connected_to {
-                        to_check.push_back(to.node);
-                    }
-                }
             }
             if !has_fuel {
                 log::debug!("Launch impossible: no fuel connected");
@@ -95,8 +228,12 @@ impl Model {
 
         log::debug!("Launch the drill!");
         self.phase = Phase::Drill;
+        self.run = RunStats {
+            layout_hash: self.nodes.layout_hash(),
+            ..default()
+        };
         self.drill.target_speed = self.config.drill_speed;
-        self.context.assets.sounds.start.play();
+        self.context.sfx.play(&self.context.assets.sounds.start, SfxCategory::Drill);
         Ok(())
     }
 
@@ -125,6 +262,85 @@ impl Model {
             duration: Bounded::new_max(self.config.sprint_duration),
         });
         self.drill.speed += self.config.sprint_boost;
+        self.run.sprints += 1;
+    }
+
+    /// Pull the drill back to the surface with the cargo intact.
+    pub fn recall_drill(&mut self, node_i: usize) {
+        let Phase::Drill = self.phase else { return };
+
+        let Some(node) = self.nodes.nodes.get(node_i) else {
+            return;
+        };
+        if !matches!(node.kind, NodeKind::Winch) || !node.is_powered {
+            return;
+        }
+
+        self.end_drill_phase(RunEnd::Recalled);
+    }
+
+    /// Select the next checkpoint unlocked for the elevator.
+    pub fn cycle_elevator(&mut self, node_i: usize) {
+        let Phase::Setup = self.phase else { return };
+
+        let best_depth = self.context.best_depth().unwrap_or(Coord::ZERO);
+        let unlocked = self
+            .config
+            .checkpoints
+            .iter()
+            .take_while(|&&depth| depth <= best_depth)
+            .count();
+
+        let Some(node) = self.nodes.nodes.get_mut(node_i) else {
+            return;
+        };
+        let NodeKind::Elevator { checkpoint } = &mut node.kind else {
+            return;
+        };
+        *checkpoint = (*checkpoint + 1) % (unlocked + 1);
+    }
+
+    /// Close the run summary and prepare the next run.
+    pub fn dismiss_summary(&mut self) {
+        let Phase::Summary { .. } = self.phase else {
+            return;
+        };
+        self.phase = Phase::Setup;
+        self.generate_level();
+    }
+
+    pub fn save(&self) -> SaveState {
+        SaveState {
+            // Continue from where the level generation is now
+            seed: self.level_rng.clone().gen(),
+            money: self.money,
+            nodes: self.nodes.nodes.clone(),
+            ground_level: self.ground_level,
+            discount: self.discount,
+            unlocks: Some(self.config.unlocks.clone()),
+            shops: Some([
+                self.config.shop_0.clone(),
+                self.config.shop_1.clone(),
+                self.config.shop_2.clone(),
+            ]),
+        }
+    }
+
+    pub fn load_save(&mut self, save: SaveState) {
+        self.phase = Phase::Setup;
+        self.money = save.money;
+        self.nodes.nodes = save.nodes;
+        self.ground_level = save.ground_level;
+        self.discount = save.discount;
+        if let Some(unlocks) = save.unlocks {
+            self.config.unlocks = unlocks;
+        }
+        if let Some([shop_0, shop_1, shop_2]) = save.shops {
+            self.config.shop_0 = shop_0;
+            self.config.shop_1 = shop_1;
+            self.config.shop_2 = shop_2;
+        }
+        self.set_seed(save.seed);
     }
 
     pub fn purchase_item(&mut self, index: usize) {
@@ -134,12 +350,14 @@ impl Model {
             return;
         };
 
-        if item.item.cost > self.money {
+        let cost = self.item_cost(&item.item);
+        if cost > self.money {
             return; // Cannot afford
         }
 
         let item = self.shop.remove(index);
-        self.money -= item.item.cost;
+        self.money -= cost;
+        self.discount = None;
         let shop = match item.tier {
             0 => &mut self.config.shop_0,
             1 => &mut self.config.shop_1,
@@ -149,7 +367,23 @@ impl Model {
             item.sold_out = true;
         }
 
-        let kind = match item.item.node {
+        self.spawn_node(item.item.node);
+        self.context.sfx.play(&self.context.assets.sounds.purchase, SfxCategory::Ui);
+    }
+
+    /// The cost of the item after the discount.
+    pub fn item_cost(&self, item: &ShopItem) -> Money {
+        match self.discount {
+            Some(discount) => {
+                (r32(item.cost as f32) * (R32::ONE - discount)).ceil().as_f32() as Money
+            }
+            None => item.cost,
+        }
+    }
+
+    /// Add a new unconnected node to the center of the node view.
+    fn spawn_node(&mut self, node: ShopNode) {
+        let kind = match node {
             ShopNode::FuelSmall => NodeKind::Fuel(Bounded::new_max(self.config.fuel_small_amount)),
             ShopNode::Fuel => NodeKind::Fuel(Bounded::new_max(self.config.fuel_normal_amount)),
             ShopNode::TurnLeft => NodeKind::TurnLeft,
@@ -164,6 +398,23 @@ impl Model {
             ShopNode::CoalFuel => {
                 NodeKind::CoalFuel(Bounded::new_zero(self.config.fuel_normal_amount))
             }
+            ShopNode::Armor => NodeKind::Armor { level: 0 },
+            ShopNode::Repair => NodeKind::Repair(Bounded::new_max(self.config.repair_amount)),
+            ShopNode::CargoExpansion => NodeKind::CargoExpansion,
+            ShopNode::Winch => NodeKind::Winch,
+            ShopNode::Elevator => NodeKind::Elevator { checkpoint: 0 },
+            ShopNode::Filter => NodeKind::Filter,
+            ShopNode::Cooling => NodeKind::Cooling,
+            ShopNode::Pump => NodeKind::Pump,
+            ShopNode::Zapper => NodeKind::Zapper {
+                cooldown: Bounded::new_zero(self.config.zapper_cooldown),
+            },
+            ShopNode::Scanner => NodeKind::Scanner {
+                level: 0,
+                filter: None,
+                cooldown: Bounded::new_zero(self.config.scanner_interval),
+            },
+            ShopNode::Tuner(resource) => NodeKind::Tuner { resource },
         };
 
         let position = self.nodes.bounds.center();
@@ -182,7 +433,7 @@ impl Model {
             blink: Bounded::new_max(r32(0.0)),
             position,
             kind,
-            connections: match item.item.node {
+            connections: match node {
                 ShopNode::FuelSmall | ShopNode::Fuel | ShopNode::CoalFuel => {
                     mk_cons(&[((0.0, 0.5), ConnectionKind::Fuel)])
                 }
@@ -191,30 +442,93 @@ impl Model {
                     ((0.5, 1.0), ConnectionKind::Drill),
                     ((1.0, 0.5), ConnectionKind::Normal),
                 ]),
-                ShopNode::Battery | ShopNode::Sprint => mk_cons(&[
+                ShopNode::Battery
+                | ShopNode::Sprint
+                | ShopNode::Repair
+                | ShopNode::CargoExpansion
+                | ShopNode::Winch
+                | ShopNode::Elevator
+                | ShopNode::Filter
+                | ShopNode::Cooling
+                | ShopNode::Pump
+                | ShopNode::Zapper => mk_cons(&[
                     ((0.0, 0.5), ConnectionKind::Normal),
                     ((1.0, 0.5), ConnectionKind::Normal),
                 ]),
-                ShopNode::Upgrade => mk_cons(&[
+                ShopNode::Upgrade | ShopNode::Tuner(_) => mk_cons(&[
                     ((0.5, 0.0), ConnectionKind::Upgrade),
                     ((0.5, 1.0), ConnectionKind::Upgrade),
                 ]),
-                ShopNode::Speed | ShopNode::Light => mk_cons(&[
+                ShopNode::Speed
+                | ShopNode::Light
+                | ShopNode::Armor
+                | ShopNode::Scanner => mk_cons(&[
                     ((0.0, 0.5), ConnectionKind::Drill),
                     ((0.5, 1.0), ConnectionKind::Upgrade),
                     ((1.0, 0.5), ConnectionKind::Fuel),
                 ]),
             },
         });
-        self.context.assets.sounds.purchase.play();
     }
 
-    fn end_drill_phase(&mut self) {
+    fn end_drill_phase(&mut self, reason: RunEnd) {
         let Phase::Drill = self.phase else { return };
-        log::debug!("Ending drill phase");
-        self.context.assets.sounds.stop.play();
-        self.phase = Phase::Setup;
-        self.generate_level();
+        log::debug!("Ending drill phase: {:?}", reason);
+        self.context.sfx.play(&self.context.assets.sounds.stop, SfxCategory::Drill);
+
+        // Deliver the cargo to the surface
+        let loss = match reason {
+            RunEnd::OutOfFuel => self.config.cargo_loss_out_of_fuel,
+            RunEnd::Destroyed => self.config.cargo_loss_destroyed,
+            RunEnd::Recalled | RunEnd::TimeOut => R32::ZERO,
+        };
+        self.sell_cargo(loss);
+        self.context.record_run(RunRecord {
+            end: reason,
+            stats: self.run.clone(),
+        });
+
+        self.drill.sprint = None;
+        self.phase = Phase::Summary { reason };
+    }
+
+    fn update_run_time(&mut self, delta_time: FloatTime) {
+        let Phase::Drill = self.phase else { return };
+
+        self.run.duration += delta_time;
+        if self.run.duration >= self.config.max_run_time {
+            self.end_drill_phase(RunEnd::TimeOut);
+        }
+    }
+
+    /// Sell the contents of the cargo hold, losing a fraction of every kind of ore.
+    fn sell_cargo(&mut self, loss: R32) -> Money {
+        let mut value = 0;
+        for (kind, count) in std::mem::take(&mut self.cargo.resources) {
+            let kept = (r32(count as f32) * (R32::ONE - loss)).floor().as_f32() as ResourceCount;
+            let price = self
+                .config
+                .minerals
+                .get(&MineralKind::Resource(kind))
+                .map_or(0, |config| config.value);
+            value += kept * price;
+        }
+        if value <= 0 {
+            return 0;
+        }
+
+        self.money += value;
+        self.run.money += value;
+        self.floating_texts.insert(FloatingText {
+            text: format!("+{}", value).into(),
+            position: self.drill.collider.position + vec2(0.0, 1.0).as_r32(),
+            velocity: vec2(0.0, 0.5).as_r32(),
+            size: r32(1.5),
+            color: self.context.palette().gold_text,
+            lifetime: Bounded::new_max(r32(2.0)),
+        });
+        self.context.sfx.play(&self.context.assets.sounds.purchase, SfxCategory::Drill);
+        value
     }
 
     fn update_nodes(&mut self, delta_time: FloatTime) {
@@ -225,6 +539,7 @@ impl Model {
             Power,
             Upgrade,
             Battery,
+            CargoExpansion,
         }
         let count_nodes = |nodes: &Nodes, index: usize, kind: CountNode| -> usize {
             let mut to_check = VecDeque::new();
@@ -241,7 +556,10 @@ impl Model {
                 match (&kind, &node.kind) {
                     (CountNode::Power, NodeKind::Power)
                     | (CountNode::Upgrade, NodeKind::Upgrade)
-                    | (CountNode::Battery, NodeKind::Battery) => upgrades += 1,
+                    | (CountNode::Battery, NodeKind::Battery)
+                    | (CountNode::CargoExpansion, NodeKind::CargoExpansion) => upgrades += 1,
+                    // Tuners sit in the upgrade chain without counting as a level
+                    (CountNode::Upgrade, NodeKind::Tuner { .. }) => {}
                     (CountNode::Upgrade, _) if i != index => continue,
                     _ => {}
                 }
@@ -258,9 +576,14 @@ impl Model {
         let mut shop_i = 0;
         let mut drill_i = 0;
         let mut vision_i = None;
+        let mut scanner_i = None;
         let mut speed_i = None;
+        let mut armor_i = None;
         let mut left_i = None;
         let mut right_i = None;
+        let mut cargo_i = None;
+        let mut elevator_i = None;
+        self.drill.protected_from.clear();
         for node_i in 0..self.nodes.nodes.len() {
             // Power state
             let power = count_nodes(&self.nodes, node_i, CountNode::Power);
@@ -284,18 +607,45 @@ impl Model {
                 NodeKind::Shop { .. } => shop_i = node_i,
                 NodeKind::Drill { .. } => drill_i = node_i,
                 NodeKind::Vision { .. } => vision_i = Some(node_i),
+                NodeKind::Scanner { .. } => scanner_i = Some(node_i),
                 NodeKind::Speed { .. } => speed_i = Some(node_i),
+                NodeKind::Armor { .. } => armor_i = Some(node_i),
                 NodeKind::TurnLeft => left_i = Some(node_i),
                 NodeKind::TurnRight => right_i = Some(node_i),
+                NodeKind::Cargo => cargo_i = Some(node_i),
+                NodeKind::Elevator { .. } => elevator_i = Some(node_i),
                 _ => {}
             }
 
+            // Hazard protection
+            if node.is_powered {
+                let protection = match node.kind {
+                    NodeKind::Filter => Some(HazardKind::Gas),
+                    NodeKind::Cooling => Some(HazardKind::Lava),
+                    NodeKind::Pump => Some(HazardKind::Water),
+                    _ => None,
+                };
+                self.drill.protected_from.extend(protection);
+            }
+
             // Cooldown
             if let Phase::Drill = self.phase {
-                if let NodeKind::Sprint { cooldown } = &mut node.kind {
-                    if self.drill.sprint.is_none() {
+                match &mut node.kind {
+                    NodeKind::Sprint { cooldown } => {
+                        if self.drill.sprint.is_none() {
+                            cooldown.change(-delta_time);
+                        }
+                    }
+                    NodeKind::Zapper { cooldown } => {
+                        // Recharges from the power network
+                        if node.is_powered {
+                            cooldown.change(-delta_time);
+                        }
+                    }
+                    NodeKind::Scanner { cooldown, .. } => {
                         cooldown.change(-delta_time);
                     }
+                    _ => {}
                 }
             }
         }
@@ -333,17 +683,37 @@ impl Model {
                 }
             }
 
-            if count_nodes(&self.nodes, vision_i, CountNode::Power) > 0 {
-                self.drill.vision_radius = match vision_upgrades {
-                    0 => self.config.vision_0,
-                    1 => self.config.vision_1,
-                    _ => self.config.vision_2,
-                };
+            let (radius, cone) = if count_nodes(&self.nodes, vision_i, CountNode::Power) > 0 {
+                match vision_upgrades {
+                    0 => (self.config.vision_0, self.config.vision_cone_0),
+                    1 => (self.config.vision_1, self.config.vision_cone_1),
+                    _ => (self.config.vision_2, self.config.vision_cone_2),
+                }
             } else {
-                self.drill.vision_radius = self.config.vision;
-            }
+                (self.config.vision, self.config.vision_cone)
+            };
+            self.drill.vision_radius = radius;
+            self.drill.vision_cone = Angle::from_degrees(cone);
         } else {
             self.drill.vision_radius = self.config.vision;
+            self.drill.vision_cone = Angle::from_degrees(self.config.vision_cone);
+        }
+
+        // Update scanner level
+        if let Some(scanner_i) = scanner_i {
+            let scanner_upgrades = count_nodes(&self.nodes, scanner_i, CountNode::Upgrade);
+            let tuned = self.nodes.upgrade_chain(scanner_i).into_iter().find_map(|i| {
+                match self.nodes.nodes[i].kind {
+                    NodeKind::Tuner { resource } => Some(resource),
+                    _ => None,
+                }
+            });
+            if let Some(node) = self.nodes.nodes.get_mut(scanner_i) {
+                if let NodeKind::Scanner { level, filter, .. } = &mut node.kind {
+                    *level = scanner_upgrades;
+                    *filter = tuned;
+                }
+            }
         }
 
         // Update speed level
@@ -368,6 +738,60 @@ impl Model {
             self.drill.max_speed = self.config.drill_speed;
         }
 
+        // Update hull level
+        let max_hull = if let Some(armor_i) = armor_i {
+            let armor_upgrades = count_nodes(&self.nodes, armor_i, CountNode::Upgrade);
+            if let Some(node) = self.nodes.nodes.get_mut(armor_i) {
+                if let NodeKind::Armor { level } = &mut node.kind {
+                    *level = armor_upgrades;
+                }
+            }
+
+            if count_nodes(&self.nodes, armor_i, CountNode::Power) > 0 {
+                match armor_upgrades {
+                    0 => self.config.hull_0,
+                    1 => self.config.hull_1,
+                    _ => self.config.hull_2,
+                }
+            } else {
+                self.config.hull
+            }
+        } else {
+            self.config.hull
+        };
+        if max_hull != self.drill.hull.max() {
+            let hull = self.drill.hull.value().min(max_hull);
+            self.drill.hull = Bounded::new(hull, Coord::ZERO..=max_hull);
+        }
+        if let Phase::Setup = self.phase {
+            self.drill.hull.set_ratio(r32(1.0));
+        }
+
+        // Update cargo capacity
+        let expansions = cargo_i.map_or(0, |cargo_i| {
+            count_nodes(&self.nodes, cargo_i, CountNode::CargoExpansion)
+        });
+        self.cargo.capacity =
+            self.config.cargo_capacity + expansions as ResourceCount * self.config.cargo_expansion;
+
+        // Update the starting depth
+        if let Phase::Setup = self.phase {
+            let checkpoint = elevator_i
+                .and_then(|i| self.nodes.nodes.get(i))
+                .filter(|node| node.is_powered)
+                .and_then(|node| match node.kind {
+                    NodeKind::Elevator { checkpoint } => checkpoint.checked_sub(1),
+                    _ => None,
+                })
+                .and_then(|i| self.config.checkpoints.get(i).copied());
+            let ground_level = checkpoint.map_or(Coord::ZERO, |depth| -depth);
+            if ground_level != self.ground_level {
+                log::debug!("Moving the surface base to {}", ground_level);
+                self.ground_level = ground_level;
+                self.generate_level();
+            }
+        }
+
         // Update turns
         self.drill.can_turn_left =
             left_i.is_some_and(|left_i| count_nodes(&self.nodes, left_i, CountNode::Power) > 0);
@@ -375,8 +799,9 @@ impl Model {
             right_i.is_some_and(|right_i| count_nodes(&self.nodes, right_i, CountNode::Power) > 0);
     }
 
-    fn update_camera(&mut self, _delta_time: FloatTime) {
+    fn update_camera(&mut self, delta_time: FloatTime) {
         self.camera.center = self.drill.collider.position.as_f32();
+        self.shake = (self.shake - self.config.shake_decay * delta_time).max(R32::ZERO);
     }
 
     fn move_drill(&mut self, delta_time: FloatTime) {
@@ -386,10 +811,20 @@ impl Model {
         } else {
             self.drill.max_speed
         };
+        if self.drill.drilling {
+            self.drill.target_speed *= self.config.drilling_slowdown;
+        }
+        if let Some(biome) = self.config.biome_at(self.drill.collider.position.y) {
+            self.drill.target_speed *= biome.drill_speed;
+        }
+        if self.drill.in_cave {
+            self.drill.target_speed *= self.config.cave_speed_boost;
+        }
+        if self.drill.hazards.contains(&HazardKind::Water) {
+            self.drill.target_speed *= R32::ONE - self.hazard_strength(HazardKind::Water);
+        }
         self.drill.speed += (self.drill.target_speed - self.drill.speed)
             .clamp_abs(self.config.drill_acceleration * delta_time);
-        self.drill.collider.position +=
-            self.drill.collider.rotation.unit_vec() * self.drill.speed * delta_time;
 
         // Update sprint
         if let Some(sprint) = &mut self.drill.sprint {
@@ -404,117 +839,712 @@ impl Model {
                 self.drill.sprint = None;
             }
         }
+
+        let delta = self.drill.collider.rotation.unit_vec() * self.drill.speed * delta_time;
+        self.sweep_drill(delta);
+        self.carve_tunnel();
+        self.run.max_depth = self.run.max_depth.max(-self.drill.collider.position.y);
     }
 
-    fn collide_drill(&mut self, _delta_time: FloatTime) {
+    /// Extend the tunnel behind the drill, merging straight stretches into a single segment.
+    fn carve_tunnel(&mut self) {
+        if self.drill.in_cave {
+            // Nothing to carve, start a new tunnel on the way out
+            if self.tunnels.last().map_or(false, |tunnel| !tunnel.points.is_empty()) {
+                self.tunnels.push(Tunnel::default());
+            }
+            return;
+        }
+
+        let position = self.drill.collider.position;
+        if self.tunnels.is_empty() {
+            self.tunnels.push(Tunnel::default());
+        }
+        let Some(tunnel) = self.tunnels.last_mut() else {
+            return;
+        };
+        let points = &mut tunnel.points;
+        let Some(&last) = points.last() else {
+            points.push(position);
+            return;
+        };
+        if (position - last).len() < self.config.tunnel_resolution {
+            return;
+        }
+
+        if let [.., a, b] = points.as_slice() {
+            let straight = vec2::dot(
+                (*b - *a).normalize_or_zero(),
+                (position - *b).normalize_or_zero(),
+            ) > r32(0.999);
+            if straight {
+                *points.last_mut().unwrap() = position;
+                return;
+            }
+        }
+        points.push(position);
+    }
+
+    /// Move the drill by `delta`, collecting every mineral along the way
+    /// and stopping at the first obstacle or larger deposit.
+    fn sweep_drill(&mut self, delta: vec2<Coord>) {
+        let drill_aabb = self.drill.collider.compute_aabb();
+        let sweep_aabb = Aabb2 {
+            min: drill_aabb.min + delta.map(|x| x.min(Coord::ZERO)),
+            max: drill_aabb.max + delta.map(|x| x.max(Coord::ZERO)),
+        };
+
+        // Minerals that already overlap the drill are resolved in `collide_drill`
+        let mut hits: Vec<(usize, Cast)> = self
+            .minerals
+            .iter()
+            .enumerate()
+            .filter(|(_, mineral)| {
+                mineral.collider.compute_aabb().intersects(&sweep_aabb)
+                    && !mineral.collider.check(&self.drill.collider)
+            })
+            .filter_map(|(i, mineral)| {
+                self.drill
+                    .collider
+                    .cast(delta, &mineral.collider)
+                    .map(|cast| (i, cast))
+            })
+            .collect();
+        hits.sort_by_key(|(_, cast)| cast.time);
+
+        let mut travel = Coord::ONE;
+        let mut obstacle = None;
+        let mut collected = Vec::new();
+        for (i, cast) in hits {
+            let mineral = &self.minerals[i];
+            if !self.is_drillable(mineral.kind) {
+                travel = cast.time;
+                obstacle = Some(cast.normal);
+                break;
+            }
+            if mineral.amount > 1 {
+                // Larger deposits are drilled over time in `collide_drill`, so stop
+                // just inside it instead of passing through at high speed
+                let bite = self.config.mineral_size * r32(0.1) / delta.len();
+                travel = (cast.time + bite).min(Coord::ONE);
+                break;
+            }
+            collected.push(i);
+        }
+        self.drill.collider.position += delta * travel;
+
+        self.collect_minerals(collected);
+        if let Some(normal) = obstacle {
+            if let Some(speed) = self.bounce_drill(normal) {
+                self.impact_drill(speed);
+            }
+        }
+    }
+
+    fn is_drillable(&self, kind: MineralKind) -> bool {
+        match kind {
+            MineralKind::Resource(kind) => kind <= self.drill.drill_level,
+            MineralKind::Rock => false,
+        }
+    }
+
+    /// Reflect the drill heading off an obstacle with the given contact normal.
+    /// Returns the impact speed if the drill was moving into the obstacle.
+    fn bounce_drill(&mut self, normal: vec2<Coord>) -> Option<Coord> {
+        let direction = self.drill.collider.rotation.unit_vec();
+        let dot = vec2::dot(direction, normal);
+        if dot <= Coord::ZERO {
+            return None;
+        }
+        self.drill.collider.rotation = (direction - normal * dot * r32(2.0)).arg();
+        let impact = self.drill.speed * dot;
+        self.drill.speed *= self.config.bounce_elasticity;
+        self.run.bounces += 1;
+        Some(impact)
+    }
+
+    fn impact_drill(&mut self, speed: Coord) {
+        self.context.sfx.play(&self.context.assets.sounds.collide, SfxCategory::Drill);
+        self.damage_hull(self.config.hull_damage * speed);
+    }
+
+    fn collide_drill(&mut self, delta_time: FloatTime) {
+        let Phase::Drill = self.phase else { return };
+
         // Level bounds
         let aabb = self.drill.collider.compute_aabb();
-        if aabb.min.x < self.bounds.min.x || aabb.max.x > self.bounds.max.x {
-            self.drill.collider.rotation =
-                Angle::from_degrees(r32(180.0)) - self.drill.collider.rotation;
-            self.context.assets.sounds.bounce.play();
+        let wall_offset = if aabb.min.x < self.bounds.min.x {
+            Some(self.bounds.min.x - aabb.min.x)
+        } else if aabb.max.x > self.bounds.max.x {
+            Some(self.bounds.max.x - aabb.max.x)
+        } else {
+            None
+        };
+        if let Some(offset) = wall_offset {
+            // Push back inside and flip only if still heading into the wall
+            self.drill.collider.position.x += offset;
+            if self.drill.collider.rotation.unit_vec().x * offset < Coord::ZERO {
+                self.drill.collider.rotation =
+                    Angle::from_degrees(r32(180.0)) - self.drill.collider.rotation;
+                self.context.sfx.play(&self.context.assets.sounds.bounce, SfxCategory::Drill);
+                self.run.bounces += 1;
+            }
         }
 
         // Minerals
         let mut collected = Vec::new();
+        let mut deposits = Vec::new();
         let mut collisions = HashSet::new();
-        let mut bounce = false;
-        for (i, mineral) in self.minerals.iter().enumerate() {
+        let mut impact_speed = None;
+        for i in 0..self.minerals.len() {
+            let mineral = &self.minerals[i];
             if !mineral.collider.check(&self.drill.collider) {
                 continue;
             }
-            collisions.insert(i);
-            if self.drill.colliding_with.contains(&i) {
+            collisions.insert(mineral.id);
+
+            if self.is_drillable(mineral.kind) {
+                if mineral.amount > 1 {
+                    // Chew through
+                    deposits.push(i);
+                } else if !self.drill.colliding_with.contains(&mineral.id) {
+                    // Collect
+                    collected.push(i);
+                }
                 continue;
             }
 
-            match mineral.kind {
-                MineralKind::Resource(kind) => {
-                    if kind <= self.drill.drill_level {
-                        // Collect
-                        collected.push(i);
-                    } else {
-                        // Bounce
-                        bounce = true;
-                        self.drill.speed = r32(0.5);
-                    }
-                }
-                MineralKind::Rock => {
-                    // Bounce
-                    bounce = true;
-                    self.drill.speed = r32(0.5);
-                }
+            // Push out of the obstacle
+            let Some(collision) = self.drill.collider.collide(&mineral.collider) else {
+                continue;
+            };
+            self.drill.collider.position -= collision.normal * collision.penetration;
+            if let Some(speed) = self.bounce_drill(collision.normal) {
+                impact_speed.get_or_insert(speed);
             }
         }
         self.drill.colliding_with = collisions;
 
-        let mut rng = thread_rng();
-        let palette = &self.palette;
-        if bounce {
-            self.context.assets.sounds.collide.play();
-        } else if !collected.is_empty() {
-            self.context.assets.sounds.pickup.play();
+        // Caves
+        let position = self.drill.collider.position;
+        self.drill.in_cave = self.caves.iter().any(|cave| cave.contains(position));
+
+        self.drill.drilling = !deposits.is_empty();
+        self.drill_deposits(&deposits, delta_time);
+        collected.extend(
+            deposits
+                .into_iter()
+                .filter(|&i| self.minerals[i].amount <= 0),
+        );
+
+        self.collect_minerals(collected);
+        if let Some(speed) = impact_speed {
+            self.impact_drill(speed);
         }
-        for i in collected.into_iter().rev() {
-            let mineral = self.minerals.swap_remove(i);
-            if let Some(config) = self.config.minerals.get(&mineral.kind) {
-                let value = mineral.amount * config.value;
-                let position = rng.gen_circle(mineral.collider.position, r32(0.2));
-                let speed = r32(0.5);
-                let velocity =
-                    Angle::from_degrees(r32(rng.gen_range(60.0..=120.0))).unit_vec() * speed;
-                self.money += value;
-                self.floating_texts.insert(FloatingText {
-                    text: format!("+{}", value).into(),
-                    position,
-                    velocity,
-                    size: r32(1.0),
-                    color: palette.gold_text,
-                    lifetime: Bounded::new_max(r32(1.0)),
-                });
 
-                if let MineralKind::Resource(ResourceKind::Coal) = mineral.kind {
-                    // Convert into fuel
-                    for node in &mut self.nodes.nodes {
-                        if let NodeKind::CoalFuel(fuel) = &mut node.kind {
-                            fuel.change(self.config.coal_fuel_value);
-                        }
-                    }
-                }
+        // Pickups
+        let drill = &self.drill.collider;
+        let (picked, pickups): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pickups)
+            .into_iter()
+            .partition(|pickup| pickup.collider.check(drill));
+        self.pickups = pickups;
+        for pickup in picked {
+            self.collect_pickup(pickup);
+        }
+
+        // Sell points
+        let mut sell = false;
+        for point in &mut self.sell_points {
+            if !point.used && point.collider.check(&self.drill.collider) {
+                point.used = true;
+                sell = true;
             }
         }
+        if sell {
+            self.sell_cargo(R32::ZERO);
+        }
     }
 
-    fn use_fuel(&mut self, delta_time: FloatTime) {
-        let mut checked = HashSet::new();
-        let mut to_check = VecDeque::new();
-        to_check.push_front(0);
-        while let Some(i) = to_check.pop_front() {
-            if checked.contains(&i) {
+    /// Mine units from the deposits the drill is chewing through.
+    fn drill_deposits(&mut self, deposits: &[usize], delta_time: FloatTime) {
+        let biome_speed = self
+            .config
+            .biome_at(self.drill.collider.position.y)
+            .map_or(R32::ONE, |biome| biome.drill_speed);
+        let mut mined = Vec::new();
+        for &i in deposits {
+            let Some(mineral) = self.minerals.get_mut(i) else {
                 continue;
-            }
-            let Some(node) = self.nodes.nodes.get_mut(i) else {
+            };
+            let MineralKind::Resource(kind) = mineral.kind else {
                 continue;
             };
 
-            for conn in &node.connections {
-                if let Some(i) = conn.connected_to {
-                    to_check.push_back(i.node);
+            // Faster the more the drill outclasses the ore
+            let tiers_above = (self.drill.drill_level as i64 - kind as i64).max(0);
+            let speed = self.config.drilling_speed
+                * biome_speed
+                * (R32::ONE + self.config.drilling_tier_bonus * r32(tiers_above as f32));
+            mineral.progress += speed * delta_time;
+            while mineral.progress >= R32::ONE && mineral.amount > 0 {
+                mineral.progress -= R32::ONE;
+                mineral.amount -= 1;
+                mined.push((mineral.kind, mineral.collider.position));
+            }
+            if mineral.amount > 0 {
+                mineral.collider.shape =
+                    Shape::circle(self.config.mineral_size * r32(mineral.amount as f32).sqrt());
+            }
+
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::Collect(kind),
+                density: r32(10.0) * delta_time,
+                distribution: ParticleDistribution::Circle {
+                    center: mineral.collider.position,
+                    radius: self.config.mineral_size,
+                },
+                velocity: (self.drill.collider.position - mineral.collider.position)
+                    .normalize_or_zero(),
+                ..default()
+            });
+        }
+
+        if !mined.is_empty() {
+            self.context.sfx.play(&self.context.assets.sounds.pickup, SfxCategory::Drill);
+        }
+        for (kind, position) in mined {
+            self.mine(kind, 1, position);
+        }
+    }
+
+    fn collect_minerals(&mut self, mut indices: Vec<usize>) {
+        if indices.is_empty() {
+            return;
+        }
+
+        indices.sort();
+        let mut any_mined = false;
+        for i in indices.into_iter().rev() {
+            let mineral = self.minerals.swap_remove(i);
+            if mineral.amount > 0 {
+                any_mined = true;
+                self.mine(mineral.kind, mineral.amount, mineral.collider.position);
+            }
+        }
+        if any_mined {
+            self.context.sfx.play(&self.context.assets.sounds.pickup, SfxCategory::Drill);
+        }
+    }
+
+    /// Load the mined units of a mineral into the cargo hold.
+    fn mine(&mut self, kind: MineralKind, amount: ResourceCount, position: vec2<Coord>) {
+        let MineralKind::Resource(resource) = kind else {
+            return;
+        };
+
+        let position = self.rng.gen_circle(position, r32(0.2));
+        let speed = r32(0.5);
+        let velocity =
+            Angle::from_degrees(r32(self.rng.gen_range(60.0..=120.0))).unit_vec() * speed;
+
+        let burned = resource == ResourceKind::Coal
+            && self
+                .nodes
+                .nodes
+                .iter()
+                .any(|node| matches!(node.kind, NodeKind::CoalFuel(_)));
+        let stored = if burned {
+            // Converted into fuel instead of staying in the hold, limited by the free tank space
+            let free: Fuel = self
+                .nodes
+                .nodes
+                .iter()
+                .filter_map(|node| match &node.kind {
+                    NodeKind::CoalFuel(fuel) => Some(fuel.max() - fuel.value()),
+                    _ => None,
+                })
+                .fold(Fuel::ZERO, |acc, free| acc + free);
+            let burnable = (free / self.config.coal_fuel_value).ceil().as_f32() as ResourceCount;
+            amount.min(burnable)
+        } else {
+            amount.min(self.cargo.space())
+        };
+        if stored > 0 {
+            if burned {
+                let mut fuel_left = self.config.coal_fuel_value * r32(stored as f32);
+                for node in &mut self.nodes.nodes {
+                    if let NodeKind::CoalFuel(fuel) = &mut node.kind {
+                        let added = fuel_left.min(fuel.max() - fuel.value());
+                        fuel.change(added);
+                        fuel_left -= added;
+                    }
                 }
+            } else {
+                *self.cargo.resources.entry(resource).or_insert(0) += stored;
             }
+            *self.run.ore.entry(resource).or_insert(0) += stored;
+            self.floating_texts.insert(FloatingText {
+                text: format!("+{}", stored).into(),
+                position,
+                velocity,
+                size: r32(1.0),
+                color: self
+                    .context
+                    .palette()
+                    .resources
+                    .get(&resource)
+                    .copied()
+                    .unwrap_or(self.context.palette().gold_text),
+                lifetime: Bounded::new_max(r32(1.0)),
+            });
+        }
+        if stored < amount {
+            // The rest is left behind
+            self.floating_texts.insert(FloatingText {
+                text: "FULL".into(),
+                position,
+                velocity,
+                size: r32(0.8),
+                color: self.context.palette().battery_critical,
+                lifetime: Bounded::new_max(r32(1.0)),
+            });
+        }
+    }
 
-            if let NodeKind::Fuel(fuel) | NodeKind::CoalFuel(fuel) = &mut node.kind {
-                if fuel.is_above_min() {
-                    fuel.change(-delta_time);
-                    return;
+    fn collect_pickup(&mut self, pickup: Pickup) {
+        log::debug!("Picked up {:?}", pickup.kind);
+        let text = match pickup.kind {
+            PickupKind::FuelTank => {
+                self.spawn_node(ShopNode::FuelSmall);
+                "FUEL TANK".to_owned()
+            }
+            PickupKind::VisionBoost => {
+                self.drill.vision_boost = Some(Bounded::new_max(self.config.vision_boost_duration));
+                "VISION BOOST".to_owned()
+            }
+            PickupKind::Discount => {
+                self.discount = Some(self.config.discount);
+                format!("{:.0}% OFF", self.config.discount.as_f32() * 100.0)
+            }
+            PickupKind::Unlock => {
+                if self.config.unlocks.is_empty() {
+                    // Nothing left to unlock
+                    self.spawn_node(ShopNode::FuelSmall);
+                    "FUEL TANK".to_owned()
+                } else {
+                    let item = self.config.unlocks.remove(0);
+                    let text = format!("{:?} UNLOCKED", item.node).to_uppercase();
+                    self.config.shop_0.items.insert(0, item);
+                    text
                 }
             }
+        };
 
-            checked.insert(i);
+        self.context.sfx.play(&self.context.assets.sounds.purchase, SfxCategory::Drill);
+        self.particles_queue.push(SpawnParticles {
+            kind: ParticleKind::Pickup,
+            density: r32(20.0),
+            distribution: ParticleDistribution::Circle {
+                center: pickup.collider.position,
+                radius: r32(0.5),
+            },
+            size: r32(0.05)..=r32(0.15),
+            size_function: SizeFunction::GrowShrink,
+            velocity: vec2(0.0, 1.0).as_r32(),
+            ..default()
+        });
+        self.floating_texts.insert(FloatingText {
+            text: text.into(),
+            position: pickup.collider.position + vec2(0.0, 0.5).as_r32(),
+            velocity: vec2(0.0, 1.0).as_r32(),
+            size: r32(1.2),
+            color: self.context.palette().drill,
+            lifetime: Bounded::new_max(r32(2.0)),
+        });
+    }
+
+    fn update_vision_boost(&mut self, delta_time: FloatTime) {
+        let Some(boost) = &mut self.drill.vision_boost else {
+            return;
+        };
+        boost.change(-delta_time);
+        if boost.is_min() {
+            self.drill.vision_boost = None;
+        } else {
+            self.drill.vision_radius += self.config.vision_boost;
+        }
+    }
+
+    /// Whether the position is lit by the drill's lamp or its glow.
+    pub fn is_lit(&self, position: vec2<Coord>) -> bool {
+        let delta = position - self.drill.collider.position;
+        let distance = delta.len();
+        if distance <= self.config.drill_glow {
+            return true;
+        }
+        if distance > self.drill.vision_radius {
+            return false;
+        }
+        let direction = self.drill.collider.rotation.unit_vec();
+        vec2::dot(delta / distance, direction) >= self.drill.vision_cone.cos()
+    }
+
+    /// Remember the cells lit by the drill.
+    fn explore(&mut self) {
+        let center = self.drill.collider.position;
+        let radius = self.drill.vision_radius.max(self.config.drill_glow);
+        let size = self.config.explored_cell_size;
+        let to_cell = |x: Coord| (x / size).floor().as_f32() as i64;
+        for x in to_cell(center.x - radius)..=to_cell(center.x + radius) {
+            for y in to_cell(center.y - radius)..=to_cell(center.y + radius) {
+                let cell_center = vec2(x as f32 + 0.5, y as f32 + 0.5).as_r32() * size;
+                if self.is_lit(cell_center) {
+                    self.explored.insert((x, y));
+                }
+            }
+        }
+    }
+
+    /// Whether the position has been inside the drill's vision this level.
+    pub fn is_explored(&self, position: vec2<Coord>) -> bool {
+        let cell = (position / self.config.explored_cell_size).map(|x| x.floor().as_f32() as i64);
+        self.explored.contains(&(cell.x, cell.y))
+    }
+
+    fn update_scanner(&mut self, delta_time: FloatTime) {
+        if let Some(ping) = &mut self.scanner_ping {
+            ping.lifetime.change(-delta_time);
+            if ping.lifetime.is_min() {
+                self.scanner_ping = None;
+            }
+        }
+
+        // Send a new ping when a powered scanner is ready
+        let ready = self.nodes.nodes.iter().position(|node| {
+            node.is_powered
+                && matches!(&node.kind, NodeKind::Scanner { cooldown, .. } if cooldown.is_min())
+        });
+        let Some(node_i) = ready else {
+            return;
+        };
+        if !self.drain_fuel(self.config.scanner_cost) {
+            return;
+        }
+        self.run.fuel_used += self.config.scanner_cost;
+
+        let NodeKind::Scanner { level, filter, cooldown } = &mut self.nodes.nodes[node_i].kind else {
+            return;
+        };
+        cooldown.set_ratio(r32(1.0));
+        let range = match level {
+            0 => self.config.scanner_range_0,
+            1 => self.config.scanner_range_1,
+            _ => self.config.scanner_range_2,
+        };
+        self.scanner_ping = Some(ScannerPing {
+            center: self.drill.collider.position,
+            range,
+            filter: *filter,
+            lifetime: Bounded::new_max(self.config.scanner_ping_duration),
+        });
+        self.context.sfx.play(&self.context.assets.sounds.hover, SfxCategory::Drill);
+    }
+
+    /// Strength of the hazard's effect, reduced if the drill is protected from it.
+    fn hazard_strength(&self, kind: HazardKind) -> R32 {
+        let Some(config) = self.config.hazards.get(&kind) else {
+            return R32::ZERO;
+        };
+        if self.drill.protected_from.contains(&kind) {
+            config.strength * config.protected
+        } else {
+            config.strength
+        }
+    }
+
+    fn apply_hazards(&mut self, delta_time: FloatTime) {
+        let Phase::Drill = self.phase else { return };
+
+        self.drill.hazards.clear();
+        let mut explosions = Vec::new();
+        for (i, hazard) in self.hazards.iter().enumerate() {
+            if !hazard.collider.check(&self.drill.collider) {
+                continue;
+            }
+            if let HazardKind::Gas = hazard.kind {
+                explosions.push(i);
+            } else {
+                self.drill.hazards.insert(hazard.kind);
+            }
+        }
+
+        // Gas pockets explode on contact
+        for &i in explosions.iter().rev() {
+            let hazard = self.hazards.swap_remove(i);
+            let radius = match hazard.collider.shape {
+                Shape::Circle { radius } => radius,
+                Shape::Rectangle { width, height } => width.max(height) / r32(2.0),
+            };
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::Hazard(HazardKind::Gas),
+                density: r32(10.0),
+                distribution: ParticleDistribution::Circle {
+                    center: hazard.collider.position,
+                    radius,
+                },
+                size: r32(0.1)..=r32(0.3),
+                ..default()
+            });
+            self.context.sfx.play(&self.context.assets.sounds.collide, SfxCategory::Ambient);
+            self.damage_hull(self.hazard_strength(HazardKind::Gas));
+        }
+
+        // Water limits vision
+        if self.drill.hazards.contains(&HazardKind::Water) {
+            self.drill.vision_radius *= R32::ONE - self.hazard_strength(HazardKind::Water);
+        }
+
+        // Lava boils around the drill
+        if self.drill.hazards.contains(&HazardKind::Lava) {
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::Hazard(HazardKind::Lava),
+                density: r32(5.0) * delta_time,
+                distribution: ParticleDistribution::Circle {
+                    center: self.drill.collider.position,
+                    radius: self.config.drill_size,
+                },
+                velocity: vec2(0.0, 0.5).as_r32(),
+                ..default()
+            });
+        }
+    }
+
+    fn damage_hull(&mut self, damage: Coord) {
+        let position = self.drill.collider.position;
+        self.drill.hull.change(-damage);
+        self.shake = (self.shake + damage * self.config.shake_per_damage).min(R32::ONE);
+        self.particles_queue.push(SpawnParticles {
+            kind: ParticleKind::WagonDamaged,
+            distribution: ParticleDistribution::Circle {
+                center: position,
+                radius: self.config.drill_size,
+            },
+            ..default()
+        });
+
+        if self.drill.hull.is_min() {
+            log::debug!("Drill destroyed");
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::WagonDestroyed,
+                density: r32(20.0),
+                distribution: ParticleDistribution::Circle {
+                    center: position,
+                    radius: self.config.drill_size * r32(2.0),
+                },
+                size: r32(0.1)..=r32(0.3),
+                ..default()
+            });
+            self.end_drill_phase(RunEnd::Destroyed);
+        }
+    }
+
+    fn repair_hull(&mut self, delta_time: FloatTime) {
+        let Phase::Drill = self.phase else { return };
+
+        let mut missing = self.drill.hull.max() - self.drill.hull.value();
+        if missing <= Coord::ZERO {
+            return;
+        }
+
+        let mut repaired = Coord::ZERO;
+        for node in &mut self.nodes.nodes {
+            if !node.is_powered {
+                continue;
+            }
+            if let NodeKind::Repair(charge) = &mut node.kind {
+                let amount = (self.config.repair_speed * delta_time)
+                    .min(charge.value())
+                    .min(missing);
+                charge.change(-amount);
+                missing -= amount;
+                repaired += amount;
+            }
+        }
+
+        if repaired > Coord::ZERO {
+            self.drill.hull.change(repaired);
+            self.particles_queue.push(SpawnParticles {
+                kind: ParticleKind::WagonHealing,
+                density: r32(6.0) * delta_time,
+                distribution: ParticleDistribution::Circle {
+                    center: self.drill.collider.position,
+                    radius: self.config.drill_size,
+                },
+                velocity: vec2(0.0, 0.5).as_r32(),
+                ..default()
+            });
+        }
+    }
+
+    fn use_fuel(&mut self, delta_time: FloatTime) {
+        let Phase::Drill = self.phase else { return };
+
+        let mut usage = if self.drill.in_cave {
+            delta_time * self.config.cave_fuel_usage
+        } else {
+            delta_time
+        };
+        if self.drill.hazards.contains(&HazardKind::Lava) {
+            usage += self.hazard_strength(HazardKind::Lava) * delta_time;
+        }
+        usage += self.config.pest_fuel_drain * r32(self.drill.latched_pests as f32) * delta_time;
+
+        if self.drain_fuel(usage) {
+            self.run.fuel_used += usage;
+            return;
         }
 
         // Out of fuel
-        self.end_drill_phase();
+        self.end_drill_phase(RunEnd::OutOfFuel);
+    }
+
+    /// Take fuel from the fuel nodes connected to power, emptying them in order.
+    /// Returns `false` and takes nothing if there is not enough fuel left.
+    fn drain_fuel(&mut self, amount: Fuel) -> bool {
+        let connected = self.nodes.connected_nodes(0);
+        let available = connected
+            .iter()
+            .filter_map(|&i| match &self.nodes.nodes[i].kind {
+                NodeKind::Fuel(fuel) | NodeKind::CoalFuel(fuel) => Some(fuel.value()),
+                _ => None,
+            })
+            .fold(Fuel::ZERO, |total, fuel| total + fuel);
+        if available < amount {
+            return false;
+        }
+
+        let mut left = amount;
+        for &i in &connected {
+            if let NodeKind::Fuel(fuel) | NodeKind::CoalFuel(fuel) = &mut self.nodes.nodes[i].kind {
+                let taken = left.min(fuel.value());
+                fuel.change(-taken);
+                left -= taken;
+            }
+        }
+        true
+    }
+
+    /// Total fuel stored in the nodes connected to the power network.
+    pub fn connected_fuel(&self) -> Bounded<Fuel> {
+        let mut fuel_total = Fuel::ZERO;
+        let mut fuel_max = Fuel::ZERO;
+        for i in self.nodes.connected_nodes(0) {
+            if let NodeKind::Fuel(fuel) | NodeKind::CoalFuel(fuel) = &self.nodes.nodes[i].kind {
+                fuel_total += fuel.value();
+                fuel_max += fuel.max();
+            }
+        }
+        Bounded::new(fuel_total, Fuel::ZERO..=fuel_max)
     }
 
     fn process_particles(&mut self, delta_time: FloatTime) {
@@ -549,9 +1579,10 @@ impl Model {
         for id in dead_ids {
             self.particles.remove(id);
         }
-        let spawn = self.particles_queue.drain(..).flat_map(spawn_particles);
-        for particle in spawn {
-            self.particles.insert(particle);
+        for options in std::mem::take(&mut self.particles_queue) {
+            for particle in spawn_particles(options, &mut self.rng) {
+                self.particles.insert(particle);
+            }
         }
     }
 
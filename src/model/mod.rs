@@ -1,8 +1,9 @@
 mod collider;
+mod creatures;
 mod logic;
 mod particles;
 
-pub use self::{collider::*, particles::*};
+pub use self::{collider::*, creatures::*, particles::*};
 
 use crate::prelude::*;
 
@@ -19,6 +20,10 @@ pub struct Config {
     pub coal_fuel_value: Fuel,
     /// How much to dim the node when it is not connected to power.
     pub unpowered_node_dim: f32,
+    /// How much to dim the remembered minerals outside of vision.
+    pub remembered_dim: f32,
+    /// Size of the cells in which the explored area is remembered.
+    pub explored_cell_size: Coord,
     /// For how long do the nodes blink to indicate an error.
     pub error_blink_duration: FloatTime,
     /// How fast do the nodes blink.
@@ -31,26 +36,142 @@ pub struct Config {
     pub drill_speed_2: Coord,
     pub drill_acceleration: Coord,
     pub drill_rotation_speed: Coord,
+    /// Fraction of the speed kept after bouncing off an obstacle.
+    pub bounce_elasticity: R32,
 
     pub vision: Coord,
     pub vision_0: Coord,
     pub vision_1: Coord,
     pub vision_2: Coord,
+    /// Half-angle of the drill lamp cone in degrees, for each vision level.
+    pub vision_cone: R32,
+    pub vision_cone_0: R32,
+    pub vision_cone_1: R32,
+    pub vision_cone_2: R32,
+    /// Radius of the light around the drill itself.
+    pub drill_glow: Coord,
+
+    /// Radius of the scanner ping for each level of the scanner.
+    pub scanner_range_0: Coord,
+    pub scanner_range_1: Coord,
+    pub scanner_range_2: Coord,
+    /// Time between the scanner pings.
+    pub scanner_interval: FloatTime,
+    /// How long a ping takes to expand and fade out.
+    pub scanner_ping_duration: FloatTime,
+    /// Fuel used by a single ping.
+    pub scanner_cost: Fuel,
+
+    pub hull: Coord,
+    pub hull_0: Coord,
+    pub hull_1: Coord,
+    pub hull_2: Coord,
+    /// Hull damage per unit of drill speed at the moment of impact.
+    pub hull_damage: Coord,
+    /// Screen shake added per unit of hull damage.
+    pub shake_per_damage: R32,
+    /// How fast the screen shake settles down, per second.
+    pub shake_decay: R32,
+    /// How much hull a single repair node can restore per run.
+    pub repair_amount: Coord,
+    /// How fast a repair node patches the hull, per second.
+    pub repair_speed: Coord,
 
     pub sprint_boost: Coord,
     pub sprint_duration: Coord,
     pub sprint_cooldown: Coord,
 
+    /// Radius of a single unit of mineral, deposits grow by area.
+    pub mineral_size: Coord,
+    /// How many units per second the drill mines from a deposit of its own tier.
+    pub drilling_speed: R32,
+    /// Additional drilling speed for every tier the drill is above the ore.
+    pub drilling_tier_bonus: R32,
+    /// Speed multiplier while the drill is chewing through a deposit.
+    pub drilling_slowdown: R32,
+
     pub fuel_small_amount: Fuel,
     pub fuel_normal_amount: Fuel,
+    /// Remaining fuel below which the hud shows a warning.
+    pub low_fuel_warning: Fuel,
+    /// How fast the music layers fade in and out, in volume per second.
+    pub music_fade_speed: f32,
+    /// Depth over which the music layers a biome adds fade in.
+    pub music_depth_fade: Coord,
+    /// Music playback speed while sprinting or low on fuel.
+    pub music_intense_speed: f32,
+    /// Maximum duration of a single run, after which the drill is pulled back.
+    pub max_run_time: FloatTime,
+    /// How many of the latest runs are shown in the trends on the stats page.
+    pub history_trend_runs: usize,
+    /// Runs with a fixed seed, listed on the title screen.
+    pub challenges: Vec<ChallengeConfig>,
+
+    /// How many units of ore the base cargo hold fits.
+    pub cargo_capacity: ResourceCount,
+    /// Additional capacity for every connected cargo expansion.
+    pub cargo_expansion: ResourceCount,
+    /// Fraction of the cargo lost when the drill runs out of fuel.
+    pub cargo_loss_out_of_fuel: R32,
+    /// Fraction of the cargo lost when the hull is destroyed.
+    pub cargo_loss_destroyed: R32,
+    /// Distance between the sell points underground.
+    pub sell_point_interval: Coord,
+    /// Minimum length of a single segment of the tunnel trail.
+    pub tunnel_resolution: Coord,
+
+    /// Depths the elevator can start the run from, once reached in any previous run.
+    pub checkpoints: Vec<Coord>,
 
     pub minerals: HashMap<MineralKind, MineralConfig>,
+    pub biomes: Vec<BiomeConfig>,
+
+    /// Range of the radius of a single part of a cave.
+    pub cave_size: [Coord; 2],
+    /// Range of the number of overlapping parts in a cave.
+    pub cave_parts: [usize; 2],
+    /// Range of the number of loose ore pieces on the floor of a cave.
+    pub cave_ore: [usize; 2],
+    /// Speed multiplier while the drill is flying through a cave.
+    pub cave_speed_boost: R32,
+    /// Fuel usage multiplier while the drill is inside a cave.
+    pub cave_fuel_usage: R32,
+
+    pub hazards: HashMap<HazardKind, HazardConfig>,
+
+    pub creatures: HashMap<CreatureKind, CreatureConfig>,
+    /// Distance at which the pests notice the drill.
+    pub pest_sense_range: Coord,
+    /// Fuel drained per second by every pest latched onto the drill.
+    pub pest_fuel_drain: Fuel,
+    /// Distance from the drill at which the zapper hits creatures.
+    pub zapper_range: Coord,
+    pub zapper_damage: Coord,
+    /// Time the zapper needs to recharge from the power network between zaps.
+    pub zapper_cooldown: FloatTime,
+
+    pub pickups: Vec<PickupGeneration>,
+    /// Additional vision radius from the vision boost pickup.
+    pub vision_boost: Coord,
+    pub vision_boost_duration: FloatTime,
+    /// Fraction of the cost taken off the next purchase by the discount pickup.
+    pub discount: R32,
+    /// Items added to the first shop tier by the unlock pickups, in order.
+    pub unlocks: Vec<ShopItem>,
 
     pub shop_0: ShopConfig,
     pub shop_1: ShopConfig,
     pub shop_2: ShopConfig,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChallengeConfig {
+    pub name: String,
+    pub seed: u64,
+    /// Money to start the run with.
+    pub money: Money,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ShopConfig {
     pub slots: usize,
@@ -84,25 +205,231 @@ pub enum ShopNode {
     Light,
     Sprint,
     CoalFuel,
+    Armor,
+    Repair,
+    CargoExpansion,
+    Winch,
+    Elevator,
+    Filter,
+    Cooling,
+    Pump,
+    Zapper,
+    Scanner,
+    Tuner(ResourceKind),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MineralConfig {
     pub value: Money,
     pub generation: Vec<MineralGeneration>,
+    /// Radius of the light given off by the ore.
+    #[serde(default)]
+    pub glow: Coord,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MineralGeneration {
     pub range: [Coord; 2],
     pub density: R32,
+    /// Range of units in a single deposit.
+    #[serde(default = "default_mineral_amount")]
+    pub amount: [ResourceCount; 2],
+}
+
+fn default_mineral_amount() -> [ResourceCount; 2] {
+    [1, 1]
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BiomeKind {
+    Topsoil,
+    Bedrock,
+    Caverns,
+    Magma,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BiomeConfig {
+    pub kind: BiomeKind,
+    pub range: [Coord; 2],
+    pub rock_density: R32,
+    pub cave_density: R32,
+    /// Range of the rock radius.
+    pub rock_size: [Coord; 2],
+    /// Multiplier for the drill movement and mining speed.
+    pub drill_speed: R32,
+    /// Minerals generated in addition to the global ones.
+    #[serde(default)]
+    pub minerals: Vec<BiomeMineral>,
+    #[serde(default)]
+    pub hazards: Vec<HazardGeneration>,
+    #[serde(default)]
+    pub creatures: Vec<CreatureGeneration>,
+    /// Music layers playing while drilling through the biome.
+    #[serde(default)]
+    pub music: Vec<MusicLayer>,
+}
+
+/// A single layer of the adaptive music, all layers loop in sync.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicLayer {
+    /// Calm pad, plays at the base.
+    Calm,
+    Bass,
+    Drums,
+    Arp,
+    /// Low drone for the deep biomes.
+    Deep,
+}
+
+impl MusicLayer {
+    pub const ALL: [MusicLayer; 5] = [
+        MusicLayer::Calm,
+        MusicLayer::Bass,
+        MusicLayer::Drums,
+        MusicLayer::Arp,
+        MusicLayer::Deep,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    /// A free small fuel node.
+    FuelTank,
+    /// Temporarily increases the vision radius.
+    VisionBoost,
+    /// Discount on the next purchase.
+    Discount,
+    /// Makes a new node available in the shop.
+    Unlock,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PickupGeneration {
+    pub kind: PickupKind,
+    pub range: [Coord; 2],
+    pub density: R32,
 }
 
+#[derive(Debug, Clone)]
+pub struct Pickup {
+    pub collider: Collider,
+    pub kind: PickupKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HazardKind {
+    /// Explodes on contact, damaging the hull.
+    Gas,
+    /// Burns extra fuel while inside.
+    Lava,
+    /// Slows the drill down and limits vision while inside.
+    Water,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HazardConfig {
+    /// Gas: hull damage, lava: extra fuel per second, water: fraction of speed and vision lost.
+    pub strength: R32,
+    /// Fraction of the strength left when the counter node is powered.
+    pub protected: R32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HazardGeneration {
+    pub kind: HazardKind,
+    pub density: R32,
+    /// Range of the hazard radius.
+    pub size: [Coord; 2],
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BiomeMineral {
+    pub kind: MineralKind,
+    pub density: R32,
+    #[serde(default = "default_mineral_amount")]
+    pub amount: [ResourceCount; 2],
+}
+
+impl Config {
+    /// The biome at the given height.
+    pub fn biome_at(&self, y: Coord) -> Option<&BiomeConfig> {
+        self.biome_index_at(y).map(|i| &self.biomes[i])
+    }
+
+    fn biome_index_at(&self, y: Coord) -> Option<usize> {
+        self.biomes.iter().position(|biome| {
+            let [a, b] = biome.range;
+            (a.min(b)..=a.max(b)).contains(&y)
+        })
+    }
+
+    /// Volume of the music layer while drilling at the given height, from 0 to 1.
+    /// The layers a biome adds to the one listed before it fade in with the depth
+    /// over the first `music_depth_fade` of the biome.
+    pub fn music_volume_at(&self, y: Coord, layer: MusicLayer) -> f32 {
+        let Some(i) = self.biome_index_at(y) else {
+            return if layer == MusicLayer::Bass { 1.0 } else { 0.0 };
+        };
+        let biome = &self.biomes[i];
+        if !biome.music.contains(&layer) {
+            return 0.0;
+        }
+        let Some(above) = i.checked_sub(1).map(|i| &self.biomes[i]) else {
+            return 1.0;
+        };
+        if above.music.contains(&layer) {
+            return 1.0;
+        }
+        let [a, b] = biome.range;
+        let depth = a.max(b) - y;
+        (depth / self.music_depth_fade)
+            .clamp(R32::ZERO, R32::ONE)
+            .as_f32()
+    }
+}
+
+/// Identifies a mineral for as long as it exists, unlike its index in the list.
+pub type MineralId = u64;
+
 #[derive(Debug, Clone)]
 pub struct Mineral {
+    pub id: MineralId,
     pub collider: Collider,
     pub kind: MineralKind,
     pub amount: ResourceCount,
+    /// Progress towards mining the next unit.
+    pub progress: R32,
+}
+
+/// An empty region made of overlapping parts.
+#[derive(Debug, Clone)]
+pub struct Cave {
+    pub parts: Vec<Collider>,
+}
+
+impl Cave {
+    pub fn contains(&self, point: vec2<Coord>) -> bool {
+        self.parts.iter().any(|part| part.contains(point))
+    }
+}
+
+/// A tunnel carved by the drill, as a polyline of width `drill_size`.
+#[derive(Debug, Clone, Default)]
+pub struct Tunnel {
+    pub points: Vec<vec2<Coord>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hazard {
+    pub collider: Collider,
+    pub kind: HazardKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct SellPoint {
+    pub collider: Collider,
+    pub used: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -121,6 +448,22 @@ pub enum ResourceKind {
     Gem,
 }
 
+#[derive(Debug, Clone)]
+pub struct Cargo {
+    pub capacity: ResourceCount,
+    pub resources: BTreeMap<ResourceKind, ResourceCount>,
+}
+
+impl Cargo {
+    pub fn total(&self) -> ResourceCount {
+        self.resources.values().sum()
+    }
+
+    pub fn space(&self) -> ResourceCount {
+        (self.capacity - self.total()).max(0)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Nodes {
     pub bounds: Aabb2<Coord>,
@@ -184,12 +527,78 @@ pub enum NodeKind {
         level: usize,
     },
     CoalFuel(Bounded<Fuel>),
+    Armor {
+        level: usize,
+    },
+    Repair(Bounded<Coord>),
+    Cargo,
+    CargoExpansion,
+    Winch,
+    Elevator {
+        /// Selected checkpoint, 0 being the surface.
+        checkpoint: usize,
+    },
+    /// Protects from gas explosions.
+    Filter,
+    /// Protects from lava.
+    Cooling,
+    /// Protects from water.
+    Pump,
+    /// Zaps the creatures around the drill.
+    Zapper {
+        cooldown: Bounded<FloatTime>,
+    },
+    /// Periodically pings for minerals beyond vision.
+    Scanner {
+        level: usize,
+        /// Only reveal this kind of ore.
+        filter: Option<ResourceKind>,
+        cooldown: Bounded<FloatTime>,
+    },
+    /// Makes the scanner in its upgrade chain only reveal one kind of ore.
+    Tuner {
+        resource: ResourceKind,
+    },
+}
+
+/// An expanding ring sent out by the scanner, revealing minerals as it passes.
+#[derive(Debug, Clone)]
+pub struct ScannerPing {
+    pub center: vec2<Coord>,
+    pub range: Coord,
+    pub filter: Option<ResourceKind>,
+    pub lifetime: Bounded<FloatTime>,
+}
+
+impl ScannerPing {
+    /// Current radius of the ring, reaching the full range halfway through the lifetime.
+    pub fn radius(&self) -> Coord {
+        let t = R32::ONE - self.lifetime.get_ratio();
+        self.range * (t * r32(2.0)).min(R32::ONE)
+    }
+
+    /// Visibility of the revealed minerals, fading out in the second half of the lifetime.
+    pub fn alpha(&self) -> R32 {
+        (self.lifetime.get_ratio() * r32(2.0)).min(R32::ONE)
+    }
+
+    /// Whether the ping reveals the given kind of mineral.
+    pub fn reveals(&self, kind: MineralKind) -> bool {
+        match kind {
+            MineralKind::Resource(kind) => self.filter.map_or(true, |filter| filter == kind),
+            MineralKind::Rock => false,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum Phase {
     Setup,
     Drill,
+    /// The run has ended, showing the summary before resetting the level.
+    Summary {
+        reason: RunEnd,
+    },
 }
 
 #[derive(Debug)]
@@ -205,13 +614,88 @@ pub struct Drill {
     pub max_speed: Coord,
     pub speed: Coord,
     pub target_speed: Coord,
-    pub colliding_with: HashSet<usize>,
+    /// Minerals the drill was overlapping in the last update.
+    pub colliding_with: HashSet<MineralId>,
+    /// Whether the drill is chewing through a deposit.
+    pub drilling: bool,
+    /// Whether the drill is flying through a cave.
+    pub in_cave: bool,
+    /// Hazards the drill is currently inside of.
+    pub hazards: HashSet<HazardKind>,
+    /// Hazards the powered counter nodes protect from.
+    pub protected_from: HashSet<HazardKind>,
+    /// Number of pests latched onto the drill.
+    pub latched_pests: usize,
+    pub vision_boost: Option<Bounded<FloatTime>>,
     pub sprint: Option<DrillSprint>,
+    pub hull: Bounded<Coord>,
     pub vision_radius: Coord,
+    /// Half-angle of the drill lamp cone.
+    pub vision_cone: Angle<R32>,
     pub can_turn_left: bool,
     pub can_turn_right: bool,
 }
 
+/// The reason the drill phase has ended.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RunEnd {
+    OutOfFuel,
+    Destroyed,
+    Recalled,
+    TimeOut,
+}
+
+/// Statistics of the current (or the last finished) run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunStats {
+    pub duration: FloatTime,
+    pub max_depth: Coord,
+    pub ore: BTreeMap<ResourceKind, ResourceCount>,
+    pub money: Money,
+    pub fuel_used: Fuel,
+    pub bounces: usize,
+    pub sprints: usize,
+    /// Hash of the node graph the run was launched with.
+    pub layout_hash: u64,
+}
+
+impl Default for RunStats {
+    fn default() -> Self {
+        Self {
+            duration: FloatTime::ZERO,
+            max_depth: Coord::ZERO,
+            ore: BTreeMap::new(),
+            money: 0,
+            fuel_used: Fuel::ZERO,
+            bounces: 0,
+            sprints: 0,
+            layout_hash: 0,
+        }
+    }
+}
+
+/// Progress kept between the sessions, the run in progress is not saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveState {
+    /// Seed to generate the levels from after loading.
+    pub seed: u64,
+    pub money: Money,
+    pub nodes: Vec<Node>,
+    /// Depth of the surface base, moved by the elevator.
+    #[serde(default)]
+    pub ground_level: Coord,
+    /// Discount for the next purchase.
+    #[serde(default)]
+    pub discount: Option<R32>,
+    /// Shop items the unlock pickups have not added yet.
+    #[serde(default)]
+    pub unlocks: Option<Vec<ShopItem>>,
+    /// Stock of every shop tier, with the unlocked and sold out items.
+    #[serde(default)]
+    pub shops: Option<[ShopConfig; 3]>,
+}
+
 #[derive(Debug, Clone)]
 pub enum DrillLaunchError {
     WrongPhase,
@@ -223,20 +707,44 @@ pub enum DrillLaunchError {
 pub struct Model {
     pub context: Context,
     pub config: Config,
-    pub palette: Palette,
     pub simulation_time: FloatTime,
+    /// Seed of the game, every level gets its own seed derived from it.
+    pub seed: u64,
+    pub level_rng: StdRng,
+    pub level_seed: u64,
+    /// Randomness of the simulation itself, seeded along with the levels.
+    pub rng: StdRng,
     pub phase: Phase,
+    pub run: RunStats,
+    /// The biome the drill is currently in.
+    pub biome: Option<BiomeKind>,
 
     pub camera: Camera2d,
+    /// Intensity of the screen shake, from 0 to 1.
+    pub shake: R32,
     pub bounds: Aabb2<Coord>,
     pub ground_level: Coord,
     pub depth_generated: Coord,
     pub nodes: Nodes,
 
     pub money: Money,
+    /// Discount for the next purchase.
+    pub discount: Option<R32>,
     pub shop: Vec<ShopItemTracked>,
     pub drill: Drill,
+    pub cargo: Cargo,
     pub minerals: Vec<Mineral>,
+    /// Id given to the next spawned mineral.
+    pub next_mineral_id: MineralId,
+    pub caves: Vec<Cave>,
+    pub tunnels: Vec<Tunnel>,
+    /// Cells of the map that have been inside the drill's vision.
+    pub explored: HashSet<(i64, i64)>,
+    pub hazards: Vec<Hazard>,
+    pub pickups: Vec<Pickup>,
+    pub sell_points: Vec<SellPoint>,
+    pub creatures: StructOf<Arena<Creature>>,
+    pub scanner_ping: Option<ScannerPing>,
 
     pub particles_queue: Vec<SpawnParticles>,
     pub particles: StructOf<Arena<Particle>>,
@@ -246,18 +754,25 @@ pub struct Model {
 impl Model {
     pub fn new(context: Context) -> Self {
         let config = &context.assets.config;
+        let seed = thread_rng().gen();
         let mut model = Self {
             context: context.clone(),
             config: config.clone(),
-            palette: context.assets.palette.clone(),
             simulation_time: FloatTime::ZERO,
+            seed,
+            level_rng: StdRng::seed_from_u64(seed),
+            level_seed: 0,
+            rng: StdRng::seed_from_u64(seed),
             phase: Phase::Setup,
+            run: RunStats::default(),
+            biome: None,
 
             camera: Camera2d {
                 center: vec2::ZERO,
                 rotation: Angle::ZERO,
                 fov: Camera2dFov::Vertical(15.0),
             },
+            shake: R32::ZERO,
             bounds: Aabb2::ZERO,
             ground_level: Coord::ZERO,
             depth_generated: Coord::ZERO,
@@ -344,10 +859,32 @@ impl Model {
                             connected_to: None,
                         }],
                     },
+                    Node {
+                        is_powered: false,
+                        blink: Bounded::new_max(r32(0.0)),
+                        position: Aabb2::point(vec2(6.0, -6.0))
+                            .extend_right(2.0)
+                            .extend_down(1.0)
+                            .as_r32(),
+                        kind: NodeKind::Cargo,
+                        connections: vec![
+                            NodeConnection {
+                                offset: vec2(0.0, 0.5).as_r32(),
+                                kind: ConnectionKind::Normal,
+                                connected_to: None,
+                            },
+                            NodeConnection {
+                                offset: vec2(1.0, 0.5).as_r32(),
+                                kind: ConnectionKind::Normal,
+                                connected_to: None,
+                            },
+                        ],
+                    },
                 ],
             },
 
             money: 0,
+            discount: None,
             shop: Vec::new(),
             drill: Drill {
                 collider: Collider::circle(vec2::ZERO, config.drill_size),
@@ -356,12 +893,33 @@ impl Model {
                 max_speed: config.drill_speed,
                 target_speed: Coord::ZERO,
                 colliding_with: HashSet::new(),
+                drilling: false,
+                in_cave: false,
+                hazards: HashSet::new(),
+                protected_from: HashSet::new(),
+                latched_pests: 0,
+                vision_boost: None,
                 sprint: None,
+                hull: Bounded::new_max(config.hull),
                 vision_radius: config.vision,
+                vision_cone: Angle::from_degrees(config.vision_cone),
                 can_turn_left: false,
                 can_turn_right: false,
             },
+            cargo: Cargo {
+                capacity: config.cargo_capacity,
+                resources: BTreeMap::new(),
+            },
             minerals: vec![],
+            next_mineral_id: 0,
+            caves: vec![],
+            tunnels: vec![],
+            explored: HashSet::new(),
+            hazards: vec![],
+            pickups: vec![],
+            sell_points: vec![],
+            creatures: default(),
+            scanner_ping: None,
 
             particles_queue: Vec::new(),
             particles: default(),
@@ -41,6 +41,10 @@ pub enum ParticleKind {
     WagonDamaged,
     WagonHealing,
     Collect(ResourceKind),
+    Hazard(HazardKind),
+    Pickup,
+    Creature(CreatureKind),
+    Zap,
 }
 
 #[derive(Default, Debug, Clone, Copy)]
@@ -111,11 +115,13 @@ impl Default for SpawnParticles {
     }
 }
 
-pub fn spawn_particles(options: SpawnParticles) -> impl Iterator<Item = Particle> {
-    let mut rng = thread_rng();
+pub fn spawn_particles<'a, R: Rng>(
+    options: SpawnParticles,
+    rng: &'a mut R,
+) -> impl Iterator<Item = Particle> + 'a {
     options
         .distribution
-        .sample(&mut rng, options.density)
+        .sample(rng, options.density)
         .into_iter()
         .map(move |position| {
             let velocity = rng.gen_circle(options.velocity, r32(0.2));
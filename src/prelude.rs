@@ -4,8 +4,9 @@ pub use anyhow::Result;
 pub use geng::prelude::*;
 pub use geng_utils::{bounded::Bounded, conversions::*};
 pub use stecs::{prelude::*, storage::arena::Arena};
+pub use rand::{rngs::StdRng, SeedableRng};
 pub use time::Duration;
 
-pub use std::collections::VecDeque;
+pub use std::collections::{BTreeMap, VecDeque};
 
 pub type Color = Rgba<f32>;
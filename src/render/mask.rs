@@ -10,10 +10,24 @@ pub struct MaskedRender {
 
 pub struct Masking<'a> {
     geng: &'a Geng,
+    assets: &'a Assets,
+    unit_quad: &'a ugli::VertexBuffer<draw2d::TexturedVertex>,
+    /// The light map, content is visible where it is lit.
     pub mask: ugli::Framebuffer<'a>,
     pub color: ugli::Framebuffer<'a>,
 }
 
+/// A light source drawn into the light map.
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    pub position: vec2<f32>,
+    pub radius: f32,
+    /// Brightness at the center, the light fades out linearly towards the radius.
+    pub intensity: f32,
+    /// Direction and half-angle of the cone, `None` for a point light.
+    pub cone: Option<(Angle<f32>, Angle<f32>)>,
+}
+
 impl MaskedRender {
     pub fn new(geng: &Geng, assets: &Rc<Assets>, texture_size: vec2<usize>) -> Self {
         let mut mask_texture = geng_utils::texture::new_texture(geng.ugli(), texture_size);
@@ -66,11 +80,14 @@ impl MaskedRender {
 
         Masking {
             geng: &self.geng,
+            assets: &self.assets,
+            unit_quad: &self.unit_quad,
             mask,
             color,
         }
     }
 
+    /// Draw the color buffer, fading it out where the light map is dark.
     pub fn draw(&self, parameters: ugli::DrawParameters, framebuffer: &mut ugli::Framebuffer) {
         ugli::draw(
             framebuffer,
@@ -94,4 +111,37 @@ impl Masking<'_> {
             &draw2d::Quad::new(aabb, Rgba::WHITE),
         );
     }
+
+    /// Add a light to the light map, overlapping lights add up.
+    pub fn light(&mut self, light: &Light, camera: &impl geng::AbstractCamera2d) {
+        let (direction, cone) = match light.cone {
+            Some((direction, angle)) => (direction.unit_vec(), angle.cos()),
+            None => (vec2::ZERO, -2.0),
+        };
+        let framebuffer_size = self.mask.size();
+        ugli::draw(
+            &mut self.mask,
+            &self.assets.shaders.light,
+            ugli::DrawMode::TriangleFan,
+            self.unit_quad,
+            (
+                ugli::uniforms! {
+                    u_model_matrix: mat3::translate(light.position)
+                        * mat3::scale_uniform(light.radius),
+                    u_intensity: light.intensity,
+                    u_direction: direction,
+                    u_cone: cone,
+                },
+                camera.uniforms(framebuffer_size.map(|x| x as f32)),
+            ),
+            ugli::DrawParameters {
+                blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
+                    src_factor: ugli::BlendFactor::One,
+                    dst_factor: ugli::BlendFactor::One,
+                    equation: ugli::BlendEquation::Add,
+                })),
+                ..Default::default()
+            },
+        );
+    }
 }
@@ -1,4 +1,6 @@
 pub mod mask;
+pub mod post;
+pub mod screenshot;
 pub mod util;
 
 use crate::prelude::*;
@@ -0,0 +1,218 @@
+use super::*;
+
+/// A single post-processing pass.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PostEffect {
+    Shake,
+    Bloom,
+    ChromaticAberration,
+    Quantize,
+    Crt,
+}
+
+impl PostEffect {
+    pub const ALL: [PostEffect; 5] = [
+        PostEffect::Shake,
+        PostEffect::Bloom,
+        PostEffect::ChromaticAberration,
+        PostEffect::Quantize,
+        PostEffect::Crt,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PostEffect::Shake => "SHAKE",
+            PostEffect::Bloom => "BLOOM",
+            PostEffect::ChromaticAberration => "ABERRATION",
+            PostEffect::Quantize => "QUANTIZE",
+            PostEffect::Crt => "CRT",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PostOptions {
+    /// Passes applied to the screen in order, empty to draw it as is.
+    pub chain: Vec<PostEffect>,
+    /// Screen shake strength, in fractions of the screen.
+    pub shake: f32,
+    /// Brightness above which the colors start to glow.
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    /// Offset of the red and blue channels, in pixels.
+    pub aberration: f32,
+    /// Number of levels each color channel is reduced to.
+    pub quantize_levels: f32,
+    /// How much the screen is bent, lower is more.
+    pub crt_curvature: f32,
+    pub crt_vignette: f32,
+    /// Darkening of the scanlines, 0 to turn them off.
+    pub crt_scanlines: f32,
+}
+
+impl Default for PostOptions {
+    fn default() -> Self {
+        Self {
+            chain: vec![PostEffect::Shake, PostEffect::Crt],
+            shake: 0.01,
+            bloom_threshold: 0.7,
+            bloom_intensity: 0.6,
+            aberration: 1.5,
+            quantize_levels: 6.0,
+            crt_curvature: 10.0,
+            crt_vignette: 0.2,
+            crt_scanlines: 0.1,
+        }
+    }
+}
+
+/// Runs the post-processing chain over the rendered screen.
+pub struct PostRender {
+    geng: Geng,
+    assets: Rc<Assets>,
+    unit_quad: ugli::VertexBuffer<draw2d::TexturedVertex>,
+    /// Intermediate results, the passes read from one and write into the other.
+    buffers: [ugli::Texture; 2],
+}
+
+impl PostRender {
+    pub fn new(geng: &Geng, assets: &Rc<Assets>) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            unit_quad: geng_utils::geometry::unit_quad_geometry(geng.ugli()),
+            buffers: std::array::from_fn(|_| {
+                geng_utils::texture::new_texture(geng.ugli(), vec2(1, 1))
+            }),
+        }
+    }
+
+    /// Apply the chain to the texture and draw the result into the framebuffer.
+    /// `shake` is the current screen shake intensity from 0 to 1.
+    pub fn apply(
+        &mut self,
+        texture: &ugli::Texture,
+        options: &PostOptions,
+        shake: f32,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let size = framebuffer.size();
+        for buffer in &mut self.buffers {
+            geng_utils::texture::update_texture_size(buffer, size, self.geng.ugli());
+        }
+
+        let (chain, shake) = if options.chain.is_empty() {
+            // Nothing to apply, a still shake pass just copies the screen
+            (&[PostEffect::Shake][..], 0.0)
+        } else {
+            (&options.chain[..], shake)
+        };
+
+        let pass = Pass {
+            shaders: &self.assets.shaders,
+            unit_quad: &self.unit_quad,
+            options,
+            shake,
+        };
+        let [front, back] = &mut self.buffers;
+        for (i, &effect) in chain.iter().enumerate() {
+            let source = if i == 0 { texture } else { &*front };
+            if i + 1 == chain.len() {
+                pass.draw(effect, source, framebuffer);
+            } else {
+                let target = &mut geng_utils::texture::attach_texture(back, self.geng.ugli());
+                pass.draw(effect, source, target);
+                std::mem::swap(front, back);
+            }
+        }
+    }
+}
+
+struct Pass<'a> {
+    shaders: &'a Shaders,
+    unit_quad: &'a ugli::VertexBuffer<draw2d::TexturedVertex>,
+    options: &'a PostOptions,
+    shake: f32,
+}
+
+impl Pass<'_> {
+    fn draw(
+        &self,
+        effect: PostEffect,
+        source: &ugli::Texture,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let options = self.options;
+        let shaders = self.shaders;
+        let common = ugli::uniforms! {
+            u_texture: source,
+            u_texture_size: source.size().as_f32(),
+        };
+        match effect {
+            PostEffect::Shake => {
+                // Squared intensity feels better than linear
+                let strength = self.shake * self.shake * options.shake;
+                let offset = if strength > 0.0 {
+                    thread_rng().gen_circle(vec2::ZERO, strength)
+                } else {
+                    vec2::ZERO
+                };
+                self.quad(
+                    &shaders.post_shake,
+                    (common, ugli::uniforms! { u_offset: offset }),
+                    framebuffer,
+                );
+            }
+            PostEffect::Bloom => self.quad(
+                &shaders.post_bloom,
+                (
+                    common,
+                    ugli::uniforms! {
+                        u_threshold: options.bloom_threshold,
+                        u_intensity: options.bloom_intensity,
+                    },
+                ),
+                framebuffer,
+            ),
+            PostEffect::ChromaticAberration => self.quad(
+                &shaders.post_aberration,
+                (common, ugli::uniforms! { u_offset: options.aberration }),
+                framebuffer,
+            ),
+            PostEffect::Quantize => self.quad(
+                &shaders.post_quantize,
+                (common, ugli::uniforms! { u_levels: options.quantize_levels }),
+                framebuffer,
+            ),
+            PostEffect::Crt => self.quad(
+                &shaders.crt,
+                (
+                    common,
+                    ugli::uniforms! {
+                        u_curvature: options.crt_curvature,
+                        u_vignette: options.crt_vignette,
+                        u_scanlines: options.crt_scanlines,
+                    },
+                ),
+                framebuffer,
+            ),
+        }
+    }
+
+    fn quad(
+        &self,
+        program: &ugli::Program,
+        uniforms: impl ugli::Uniforms,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        ugli::draw(
+            framebuffer,
+            program,
+            ugli::DrawMode::TriangleFan,
+            self.unit_quad,
+            uniforms,
+            ugli::DrawParameters::default(),
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+/// Largest difference of a single color channel still counted as the same pixel,
+/// to allow for small differences in the rasterization.
+const CHANNEL_TOLERANCE: u8 = 2;
+
+/// Read the contents of the framebuffer and save them as a png image.
+pub fn save_framebuffer(
+    framebuffer: &ugli::Framebuffer,
+    path: impl AsRef<std::path::Path>,
+) -> Result<()> {
+    save_image(&read_framebuffer(framebuffer), path)
+}
+
+/// Compare the contents of the framebuffer with a reference png image.
+/// Returns the number of pixels that differ.
+pub fn compare_framebuffer(
+    framebuffer: &ugli::Framebuffer,
+    reference: impl AsRef<std::path::Path>,
+) -> Result<usize> {
+    let reference = reference.as_ref();
+    let expected = image::open(reference)
+        .with_context(|| format!("when loading {}", reference.display()))?
+        .into_rgba8();
+    let actual = read_framebuffer(framebuffer);
+    if expected.dimensions() != actual.dimensions() {
+        anyhow::bail!(
+            "frame size {:?} does not match the reference {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        );
+    }
+
+    let differ = expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(a, b)| {
+            a.0.iter()
+                .zip(b.0)
+                .any(|(&a, b)| a.abs_diff(b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    Ok(differ)
+}
+
+fn read_framebuffer(framebuffer: &ugli::Framebuffer) -> image::RgbaImage {
+    let size = framebuffer.size();
+    let data = framebuffer.read_color();
+    // Framebuffer rows go bottom to top
+    image::RgbaImage::from_fn(size.x as u32, size.y as u32, |x, y| {
+        let color = data.get(x as usize, size.y - 1 - y as usize);
+        image::Rgba([color.r, color.g, color.b, color.a])
+    })
+}
+
+fn save_image(image: &image::RgbaImage, path: impl AsRef<std::path::Path>) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    image
+        .save(path)
+        .with_context(|| format!("when saving {}", path.display()))?;
+    log::info!("Saved a screenshot to {}", path.display());
+    Ok(())
+}
@@ -375,4 +375,30 @@ impl UtilRender {
                 .translate(quad.center()),
         );
     }
+
+    /// Draw a bar split into consecutive segments, each given by its fraction of the full bar.
+    pub fn draw_stacked_bar(
+        &self,
+        quad: Aabb2<f32>,
+        outline_width: f32,
+        back: Color,
+        segments: impl IntoIterator<Item = (f32, Color)>,
+        camera: &impl geng::AbstractCamera2d,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        self.draw_quad_outline(quad, outline_width, back, camera, framebuffer);
+        let mut left = quad.min.x;
+        for (ratio, color) in segments {
+            let right = (left + quad.width() * ratio).min(quad.max.x);
+            let segment = Aabb2 {
+                min: vec2(left, quad.min.y),
+                max: vec2(right, quad.max.y),
+            };
+            self.context
+                .geng
+                .draw2d()
+                .quad(framebuffer, camera, segment, color);
+            left = right;
+        }
+    }
 }